@@ -10,10 +10,15 @@ if exists("b:current_syntax")
   finish
 endif
 
-syn keyword bugConditional if
+syn keyword bugConditional if else
+syn keyword bugRepeat while
+syn keyword bugKeyword break continue
 syn keyword bugKeyword return
-syn keyword bugType str int bool
+syn keyword bugType str int bool float
 syn keyword bugBool true false
+syn keyword bugLogical and or not
+syn keyword bugStackWord dup drop swap over rot
+syn keyword bugConversion itof ftoi
 syn keyword bugKeyword f nextgroup=bugFuncName skipwhite skipempty
 syn match bugFuncName "\%(r#\)\=\%([^[:cntrl:][:space:][:punct:][:digit:]]\|_\)\%([^[:cntrl:][:punct:][:space:]]\|_\)*" display contained
 syn match bugFuncCall "\.\w\+"
@@ -21,6 +26,8 @@ syn match bugOperator display "\%(+\|-\|/\|*\|=\|\^\|&\||\|!\|>\|<\|%\)=\?"
 syntax region bugString start=/\v"/ skip=/\v\\./ end=/\v"/
 syn region bugNumber start=/\d/ skip=/\d/ end=/\s/
 syn match bugArrow display "->"
+syn match bugLineComment "//.*$"
+syn region bugBlockComment start="/\*" end="\*/" contains=bugBlockComment
 
 
 hi def link bugKeyword Keyword
@@ -28,10 +35,16 @@ hi def link bugKeyword Keyword
 hi def link bugFuncName Function
 hi def link bugFuncCall Function
 hi def link bugConditional Conditional
+hi def link bugRepeat Repeat
 hi def link bugType Type
 hi def link bugOperator Operator
+hi def link bugLogical Operator
+hi def link bugStackWord Function
+hi def link bugConversion Function
 hi def link bugNumber Number
 hi def link bugString String
 hi def link bugBool Boolean
+hi def link bugLineComment Comment
+hi def link bugBlockComment Comment
 
 let b:current_syntax = "bug"
@@ -0,0 +1,49 @@
+//! Runs a Bug script against a native function provided by the host
+//!
+//! cargo run --example embedding
+
+use std::cell::Cell;
+use std::process::exit;
+use std::rc::Rc;
+
+use bug::vm::Vm;
+use bug::{FnPrototype, Object, Type};
+
+const SOURCE: &str = "\
+f main ->
+  10 .deposit .write
+  32 .deposit .write;
+";
+
+fn main() {
+    let balance = Rc::new(Cell::new(0));
+    let account = balance.clone();
+
+    let vm = Vm::builder()
+        .native(
+            "deposit",
+            FnPrototype::make(vec![Type::Integer], Type::Integer),
+            move |args| {
+                if let Object::Int(amount) = args[0] {
+                    account.set(account.get() + amount);
+                }
+                Some(Object::Int(account.get()))
+            },
+        )
+        .build();
+
+    let program = match vm.compile(SOURCE) {
+        Ok(program) => program,
+        Err(diagnostics) => {
+            for diagnostic in diagnostics {
+                eprint!("{}", diagnostic.in_file("<embedded>").render(SOURCE));
+            }
+            exit(1);
+        }
+    };
+    if let Err(err) = vm.run(&program) {
+        eprintln!("[Error]: Runtime error: {}", err);
+        exit(1);
+    }
+    println!("host balance: {}", balance.get());
+}
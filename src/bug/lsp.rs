@@ -0,0 +1,378 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+use bug::compiler::diagnostics::Diagnostic;
+use bug::compiler::document::Document;
+use bug::compiler::span::Span;
+use bug::stdlib::{list_native_fns, Natives};
+use serde_json::{json, Value};
+
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// `CompletionItemKind.Function` of the protocol
+const FUNCTION_KIND: i64 = 3;
+
+/// Language server answering over JSON-RPC, documents are sent whole on every change and analysed
+/// again on each request. Positions are converted between the protocol's 0-based lines and UTF-16
+/// characters and the 1-based char spans of the compiler
+pub struct Server {
+    natives: Natives,
+    documents: HashMap<String, String>,
+    shutdown: bool,
+}
+
+impl Server {
+    pub fn make() -> Self {
+        Self {
+            natives: list_native_fns(),
+            documents: HashMap::new(),
+            shutdown: false,
+        }
+    }
+
+    /// Handles a request or notification, returns the messages to send back
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or("");
+        let params = &message["params"];
+        let id = match message.get("id") {
+            Some(id) => id.clone(),
+            None => return self.notify(method, params),
+        };
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "capabilities": {
+                    "textDocumentSync": 1,
+                    "hoverProvider": true,
+                    "definitionProvider": true,
+                    "completionProvider": { "triggerCharacters": ["."] },
+                },
+                "serverInfo": { "name": "bug", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/completion" => self.completion(params),
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+        };
+        let response = match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": code, "message": message },
+            }),
+        };
+        vec![response]
+    }
+
+    /// Whether the client asked to shut down before exiting
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown
+    }
+
+    fn notify(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = match params["textDocument"]["uri"].as_str() {
+            Some(uri) => uri.to_string(),
+            None => return vec![],
+        };
+        match method {
+            "textDocument/didOpen" => match params["textDocument"]["text"].as_str() {
+                Some(text) => self.documents.insert(uri.clone(), text.to_string()),
+                None => return vec![],
+            },
+            "textDocument/didChange" => match params["contentChanges"]
+                .as_array()
+                .and_then(|changes| changes.last())
+                .and_then(|change| change["text"].as_str())
+            {
+                Some(text) => self.documents.insert(uri.clone(), text.to_string()),
+                None => return vec![],
+            },
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                return vec![publish_diagnostics(&uri, "", &[])];
+            }
+            _ => return vec![],
+        };
+        let text = &self.documents[&uri];
+        let document = Document::make(text, &self.natives);
+        vec![publish_diagnostics(&uri, text, &document.diagnostics)]
+    }
+
+    /// The document a request refers to and the 1-based line and column of its position
+    fn locate(&self, params: &Value) -> Result<(Document, usize, usize), (i64, String)> {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+        let text = match self.documents.get(uri) {
+            Some(text) => text,
+            None => return Err((INVALID_PARAMS, format!("Unknown document '{}'", uri))),
+        };
+        let position = &params["position"];
+        match (position["line"].as_u64(), position["character"].as_u64()) {
+            (Some(line), Some(character)) => Ok((
+                Document::make(text, &self.natives),
+                line as usize + 1,
+                char_column(line_text(text, line as usize), character as usize) + 1,
+            )),
+            _ => Err((INVALID_PARAMS, "Missing position".to_string())),
+        }
+    }
+
+    fn hover(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (document, line, colm) = self.locate(params)?;
+        Ok(match document.hover(line, colm) {
+            Some(text) => json!({ "contents": { "kind": "markdown", "value": text } }),
+            None => Value::Null,
+        })
+    }
+
+    fn definition(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (document, line, colm) = self.locate(params)?;
+        let text = &self.documents[params["textDocument"]["uri"].as_str().unwrap_or("")];
+        Ok(match document.definition(line, colm) {
+            Some(span) => json!({
+                "uri": params["textDocument"]["uri"],
+                "range": range(text, Some(span)),
+            }),
+            None => Value::Null,
+        })
+    }
+
+    fn completion(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let (document, _, _) = self.locate(params)?;
+        let items = document
+            .completions()
+            .iter()
+            .map(|signature| {
+                json!({
+                    "label": signature.name,
+                    "kind": FUNCTION_KIND,
+                    "detail": signature.label,
+                })
+            })
+            .collect::<Vec<Value>>();
+        Ok(Value::Array(items))
+    }
+}
+
+fn publish_diagnostics(uri: &str, text: &str, diagnostics: &[Diagnostic]) -> Value {
+    let diagnostics = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            json!({
+                "range": range(text, diagnostic.span),
+                "severity": 1,
+                "source": "bug",
+                "message": format!("{}: {}", diagnostic.title, diagnostic.msg),
+            })
+        })
+        .collect::<Vec<Value>>();
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// Errors without a span, like a missing `main`, are shown at the start of the document
+fn range(text: &str, span: Option<Span>) -> Value {
+    let (line, start, end) = match span {
+        Some(span) => {
+            let line = span.line.saturating_sub(1);
+            let line_text = line_text(text, line);
+            let start = span.colm.saturating_sub(1);
+            (
+                line,
+                utf16_offset(line_text, start),
+                utf16_offset(line_text, start + span.len),
+            )
+        }
+        None => (0, 0, 0),
+    };
+    json!({
+        "start": { "line": line, "character": start },
+        "end": { "line": line, "character": end },
+    })
+}
+
+/// Text of the 0-based `line` of `text`, empty past its end
+fn line_text(text: &str, line: usize) -> &str {
+    text.lines().nth(line).unwrap_or("")
+}
+
+/// UTF-16 offset of the 0-based char `column` of `line_text`, columns past its end count one unit
+/// each
+fn utf16_offset(line_text: &str, column: usize) -> usize {
+    let chars = line_text.chars().count();
+    let units: usize = line_text.chars().take(column).map(char::len_utf16).sum();
+    units + column.saturating_sub(chars)
+}
+
+/// 0-based char column of the UTF-16 `offset` into `line_text`, the inverse of `utf16_offset`
+fn char_column(line_text: &str, offset: usize) -> usize {
+    let mut units = 0;
+    for (column, c) in line_text.chars().enumerate() {
+        if units >= offset {
+            return column;
+        }
+        units += c.len_utf16();
+    }
+    line_text.chars().count() + offset.saturating_sub(units)
+}
+
+/// Reads a message framed by a `Content-Length` header, `None` once the input ends
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let length = match length {
+        Some(length) => length,
+        None => return Err(io::Error::other("Missing Content-Length header")),
+    };
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// Serves the client on stdin and stdout until it sends `exit`, returns whether it shut down
+/// cleanly
+pub fn run() -> bool {
+    let mut server = Server::make();
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut output = io::stdout();
+    loop {
+        let message = match read_message(&mut input) {
+            Ok(Some(message)) => message,
+            Ok(None) => return false,
+            Err(err) => {
+                eprintln!("[Error]: Couldn't read message: {}", err);
+                return false;
+            }
+        };
+        if message["method"] == "exit" {
+            return server.is_shutdown();
+        }
+        for reply in server.handle(&message) {
+            if let Err(err) = write_message(&mut output, &reply) {
+                eprintln!("[Error]: Couldn't write message: {}", err);
+                return false;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ensure_requests_are_answered() {
+        let mut server = Server::make();
+        let uri = "file:///main.bug";
+        let replies = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri, "text": "f main ->\n  1 +;" } },
+        }));
+        let diagnostics = &replies[0]["params"]["diagnostics"];
+        assert_eq!(
+            json!({ "start": { "line": 1, "character": 4 }, "end": { "line": 1, "character": 5 } }),
+            diagnostics[0]["range"]
+        );
+
+        server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": uri },
+                "contentChanges": [{ "text": "f main ->\n  \"hi\" .write;" }],
+            },
+        }));
+        let replies = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "textDocument/hover",
+            "params": {
+                "textDocument": { "uri": uri },
+                "position": { "line": 1, "character": 8 },
+            },
+        }));
+        assert_eq!(
+            "```bug\nf write(any)\n```\n\nstack: []",
+            replies[0]["result"]["contents"]["value"]
+        );
+
+        let replies = server.handle(&json!({ "jsonrpc": "2.0", "id": 2, "method": "unknown" }));
+        assert_eq!(METHOD_NOT_FOUND, replies[0]["error"]["code"]);
+    }
+
+    #[test]
+    fn ensure_positions_count_utf16_units() {
+        let mut server = Server::make();
+        let uri = "file:///main.bug";
+        // each emoji takes two UTF-16 units but a single char of the compiler's columns
+        let text = format!("f main -> \"h\u{e9}{}\" .writ", "\u{1F44B}".repeat(8));
+        let replies = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri, "text": format!("{};", text) } },
+        }));
+        let diagnostics = &replies[0]["params"]["diagnostics"];
+        assert_eq!(
+            json!({ "start": { "line": 0, "character": 31 }, "end": { "line": 0, "character": 36 } }),
+            diagnostics[0]["range"]
+        );
+
+        server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": uri },
+                "contentChanges": [{ "text": format!("{}e;", text) }],
+            },
+        }));
+        let replies = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "textDocument/hover",
+            "params": {
+                "textDocument": { "uri": uri },
+                "position": { "line": 0, "character": 31 },
+            },
+        }));
+        assert_eq!(
+            "```bug\nf write(any)\n```\n\nstack: []",
+            replies[0]["result"]["contents"]["value"]
+        );
+    }
+
+    #[test]
+    fn ensure_messages_are_framed() {
+        let mut output = vec![];
+        write_message(&mut output, &json!({ "id": 1 })).unwrap();
+        assert_eq!(b"Content-Length: 8\r\n\r\n{\"id\":1}".to_vec(), output);
+        let message = read_message(&mut output.as_slice()).unwrap();
+        assert_eq!(Some(json!({ "id": 1 })), message);
+    }
+}
@@ -0,0 +1,187 @@
+mod lsp;
+mod repl;
+
+use std::io::Write;
+use std::process::exit;
+use std::{env, fs};
+
+use bug::compiler::ast::Ast;
+use bug::compiler::diagnostics::{render_all, Diagnostic};
+use bug::container;
+use bug::utils::{default_output, read_file};
+use bug::vm::Vm;
+use bug::Program;
+
+const USAGE: &str = "\
+Usage: bug <command> <file.bug> [options]
+
+Commands:
+  run <file.bug>               Compile and execute the program in-process
+  build <file.bug> [-o <path>] Compile the program to a binary for 'bvm'
+  check <file.bug>             Only check the program for errors
+  repl                         Start an interactive session, see ':help' once in it
+  lsp                          Serve the language server protocol over stdin and stdout
+  fmt [--check] <file.bug>...  Rewrite the files in the canonical style, with '--check' only
+                               list the files that aren't and fail if there is any
+
+Options:
+  --version  Print the toolkit version
+  --help     Print this message";
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => fail("No command provided"),
+    };
+
+    match command {
+        "--help" | "-h" | "help" => println!("{}", USAGE),
+        "--version" | "-V" => println!("bug {}", env!("CARGO_PKG_VERSION")),
+        "run" => {
+            let path = input_path(rest, &[]);
+            let vm = Vm::builder().build();
+            let program = compile(path);
+            if let Err(err) = vm.verify(&program) {
+                eprintln!("[Error]: Rejected {}: {}", path, err);
+                exit(1);
+            }
+            if let Err(err) = vm.run(&program) {
+                eprintln!("[Error]: Runtime error: {}", err);
+                exit(1);
+            }
+        }
+        "build" => {
+            let path = input_path(rest, &["-o"]);
+            let output = match rest.iter().position(|arg| arg == "-o") {
+                Some(index) => match rest.get(index + 1) {
+                    Some(output) => output.clone(),
+                    None => fail("Missing path after '-o'"),
+                },
+                None => default_output(path),
+            };
+            let bin = container::encode(&compile(path));
+            let written = fs::File::create(&output).and_then(|mut file| file.write_all(&bin));
+            if let Err(err) = written {
+                eprintln!("[Error]: Couldn't write file {} {}", output, err);
+                exit(1);
+            }
+        }
+        "repl" => match rest.first() {
+            Some(arg) => fail(&format!("Unexpected argument '{}'", arg)),
+            None => repl::run(),
+        },
+        "lsp" => {
+            if !lsp::run() {
+                exit(1);
+            }
+        }
+        "fmt" => format_files(rest),
+        "check" => {
+            check(input_path(rest, &[]));
+        }
+        _ => fail(&format!("Unknown command '{}'", command)),
+    }
+}
+
+/// Picks the single input file out of `args`, skipping the values of `value_options`
+fn input_path<'a>(args: &'a [String], value_options: &[&str]) -> &'a str {
+    let mut inputs = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if value_options.contains(&arg.as_str()) {
+            args.next();
+        } else if arg.starts_with('-') {
+            fail(&format!("Unknown option '{}'", arg));
+        } else {
+            inputs.push(arg.as_str());
+        }
+    }
+    match inputs[..] {
+        [path] => path,
+        [] => fail("No input file provided"),
+        _ => fail("Only one input file is allowed"),
+    }
+}
+
+fn format_files(args: &[String]) {
+    let check = args.iter().any(|arg| arg == "--check");
+    let paths = args.iter().filter(|arg| *arg != "--check");
+    let mut paths = paths.peekable();
+    if paths.peek().is_none() {
+        fail("No input file provided");
+    }
+
+    // every file is looked at even after one fails, so a single run reports all of them
+    let mut failed = false;
+    for path in paths {
+        if path.starts_with('-') {
+            fail(&format!("Unknown option '{}'", path));
+        }
+        let source = match read_file(path) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("[Error]: Couldn't read file {} {}", path, err);
+                failed = true;
+                continue;
+            }
+        };
+        let formatted = match bug::compiler::formatter::format(&source) {
+            Ok(formatted) => formatted,
+            Err(diagnostic) => {
+                eprint!("{}", diagnostic.in_file(path).render(&source));
+                failed = true;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+        if check {
+            println!("{} is not formatted", path);
+            failed = true;
+        } else if let Err(err) = fs::write(path, formatted) {
+            eprintln!("[Error]: Couldn't write file {} {}", path, err);
+            failed = true;
+        }
+    }
+    if failed {
+        exit(1);
+    }
+}
+
+fn compile(path: &str) -> Program {
+    process(path, bug::compiler::compile)
+}
+
+fn check(path: &str) -> Ast {
+    process(path, bug::compiler::check)
+}
+
+/// Reads the source at `path` and hands it to `pass`, printing its diagnostics and exiting when it
+/// fails
+fn process<T>(path: &str, pass: fn(&str) -> Result<T, Vec<Diagnostic>>) -> T {
+    let source = match read_file(path) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("[Error]: Couldn't read file {} {}", path, err);
+            exit(1);
+        }
+    };
+    match pass(&source) {
+        Ok(output) => output,
+        Err(diagnostics) => {
+            let diagnostics = diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.in_file(path))
+                .collect::<Vec<Diagnostic>>();
+            eprint!("{}", render_all(&diagnostics, &source));
+            exit(1);
+        }
+    }
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("[Error]: {}\n\n{}", msg, USAGE);
+    exit(1);
+}
@@ -0,0 +1,222 @@
+use std::io::{self, BufRead, Write};
+
+use bug::compiler::analysis::Analyser;
+use bug::compiler::ast::Ast;
+use bug::compiler::codegeneration::CodeGenerator;
+use bug::compiler::diagnostics::{render_all, Diagnostic};
+use bug::compiler::frontend::{lexer::Lexer, parser::Parser};
+use bug::vm::Vm;
+use bug::Object;
+
+const HELP: &str = "\
+Type Bug code to run it, the values left on the stack are kept for the next lines.
+Functions declared with 'f' and variables stay defined until the session ends.
+
+Commands:
+  :help  Print this message
+  :quit  Leave the REPL";
+
+/// Name of the function every line is compiled into
+const ENTRY: &str = "<repl>";
+
+/// Compiles and runs Bug one line at a time, keeping the declarations, the variables and the
+/// operand stack from one line to the next
+pub struct Repl {
+    vm: Vm,
+    analyser: Analyser,
+    generator: CodeGenerator,
+    stack: Vec<Object>,
+    locals: Vec<Object>,
+}
+
+impl Repl {
+    pub fn make() -> Self {
+        let vm = Vm::builder().build();
+        Self {
+            analyser: Analyser::make_with(vm.natives()),
+            vm,
+            generator: CodeGenerator::make(),
+            stack: vec![],
+            locals: vec![],
+        }
+    }
+
+    /// Whether `source` stops in the middle of a statement, like a function missing its `;`
+    pub fn is_incomplete(source: &str) -> bool {
+        let input = source.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut p = Parser::new(&mut l);
+        p.parse().is_err() && p.is_at_eof()
+    }
+
+    /// Runs `source` returning the stack left by it, or the rendered errors. Nothing changes when
+    /// the source doesn't compile or fails at runtime
+    pub fn eval(&mut self, source: &str) -> Result<String, String> {
+        let render = |diagnostics: Vec<Diagnostic>| {
+            let diagnostics = diagnostics
+                .into_iter()
+                .map(|diagnostic| diagnostic.in_file(ENTRY))
+                .collect::<Vec<Diagnostic>>();
+            render_all(&diagnostics, source)
+        };
+
+        let mut ast = self.parse(source).map_err(|err| render(vec![err]))?;
+        let checkpoint = self.analyser.checkpoint();
+        self.analyser
+            .analyse_line(&mut ast)
+            .map_err(|errors| render(errors.into_iter().map(Diagnostic::from).collect()))?;
+
+        let generator = self.generator.clone();
+        let program = self.generator.gen_line(ast, ENTRY);
+        let entry = program.fns.len() - 1;
+        let result = self
+            .vm
+            .resume(&program, entry, self.stack.clone(), self.locals.clone())
+            .and_then(|mut runtime| {
+                runtime.run()?;
+                Ok((runtime.stack().to_vec(), runtime.locals().to_vec()))
+            });
+        match result {
+            Ok((stack, locals)) => {
+                self.stack = stack;
+                self.locals = locals;
+                Ok(self.render_stack())
+            }
+            Err(err) => {
+                self.analyser.restore(checkpoint);
+                self.generator = generator;
+                Err(format!("[Error]: Runtime error: {}\n", err))
+            }
+        }
+    }
+
+    fn parse(&self, source: &str) -> Result<Ast, Diagnostic> {
+        let input = source.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut p = Parser::new(&mut l);
+        p.parse().map_err(Diagnostic::from)
+    }
+
+    /// The values on the stack with the types the analyser knows them by, top last
+    fn render_stack(&self) -> String {
+        let types = self.analyser.stack_types();
+        let objects = self
+            .stack
+            .iter()
+            .zip(types.iter())
+            .map(|(object, type_)| match object {
+                Object::String(x) => format!("{:?}: {}", x, type_),
+                object => format!("{}: {}", object, type_),
+            })
+            .collect::<Vec<String>>();
+        format!("[{}]", objects.join(", "))
+    }
+}
+
+/// Reads lines from stdin until `:quit` or the end of input
+pub fn run() {
+    println!(
+        "Bug {} REPL, type :help for help",
+        env!("CARGO_PKG_VERSION")
+    );
+    let mut repl = Repl::make();
+    let stdin = io::stdin();
+    let mut source = String::new();
+    loop {
+        print!("{}", if source.is_empty() { "bug> " } else { "...  " });
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+
+        if source.is_empty() {
+            match line.trim() {
+                "" => continue,
+                ":help" | ":h" => {
+                    println!("{}", HELP);
+                    continue;
+                }
+                ":quit" | ":q" => break,
+                _ => {}
+            }
+        }
+        source.push_str(&line);
+        if Repl::is_incomplete(&source) {
+            continue;
+        }
+        match repl.eval(&source) {
+            Ok(stack) => println!("{}", stack),
+            Err(err) => eprint!("{}", err),
+        }
+        source.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ensure_stack_and_declarations_persist_between_lines() {
+        let mut repl = Repl::make();
+        assert_eq!(Ok("[1: int, 2: int]".to_string()), repl.eval("1 2"));
+        assert_eq!(Ok("[3: int]".to_string()), repl.eval("+"));
+        assert!(Repl::is_incomplete("f double(int x) int ->\n  x 2 *"));
+        assert_eq!(
+            Ok("[3: int]".to_string()),
+            repl.eval("f double(int x) int ->\n  x 2 *;")
+        );
+        assert_eq!(Ok("[6: int]".to_string()), repl.eval(".double"));
+        assert_eq!(
+            Ok("[6: int, \"six\": str]".to_string()),
+            repl.eval("str s \"six\" = s")
+        );
+        assert_eq!(Ok("[6: int]".to_string()), repl.eval("drop"));
+    }
+
+    #[test]
+    fn ensure_failing_lines_change_nothing() {
+        let mut repl = Repl::make();
+        assert_eq!(Ok("[1: int, 0: int]".to_string()), repl.eval("1 0"));
+        assert!(repl.eval("/").unwrap_err().contains("Division by zero"));
+        assert!(repl.eval("f g -> 1 +;").is_err());
+        assert_eq!(Ok("[1: int, 0: int]".to_string()), repl.eval("f g -> ;"));
+        assert!(repl.eval("\"s\" +").is_err());
+        assert_eq!(Ok("[1: int]".to_string()), repl.eval("+"));
+    }
+
+    #[test]
+    fn ensure_bare_function_names_are_reported() {
+        let mut repl = Repl::make();
+        assert_eq!(Ok("[]".to_string()), repl.eval("f g() -> ;"));
+        assert!(repl
+            .eval("g")
+            .unwrap_err()
+            .contains("'g' is a function, call it with '.g'"));
+        assert_eq!(Ok("[]".to_string()), repl.eval(".g"));
+    }
+
+    #[test]
+    fn ensure_blocks_ignore_values_left_by_earlier_lines() {
+        let mut repl = Repl::make();
+        assert_eq!(Ok("[5: int]".to_string()), repl.eval("5"));
+        assert_eq!(
+            Ok("[5: int]".to_string()),
+            repl.eval("true if -> \"a\" .write;")
+        );
+    }
+
+    #[test]
+    fn ensure_functions_may_declare_names_of_repl_variables() {
+        let mut repl = Repl::make();
+        assert_eq!(Ok("[]".to_string()), repl.eval("int x 1 ="));
+        assert_eq!(
+            Ok("[]".to_string()),
+            repl.eval("f g -> int x 2 = x .write;")
+        );
+        assert_eq!(Ok("[]".to_string()), repl.eval(".g"));
+        assert_eq!(Ok("[1: int]".to_string()), repl.eval("x"));
+    }
+}
@@ -0,0 +1,117 @@
+pub const USAGE: &str = "\
+Usage: bugc [options] <file.bug>...
+
+Options:
+  -o <path>       Write the output to <path>, only allowed with a single input file
+  --emit=<kind>   What to produce, one of: tokens, ast, bytecode, bin (default)
+  --check         Only check the program for errors, nothing is emitted
+  --version       Print the compiler version
+  --help          Print this message
+
+Binaries are written next to each input file with the '.bin' extension unless '-o' is
+provided, the other emit kinds are printed to stdout.";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Emit {
+    Tokens,
+    Ast,
+    ByteCode,
+    Bin,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Compile(Options),
+    Help,
+    Version,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub inputs: Vec<String>,
+    pub output: Option<String>,
+    pub emit: Emit,
+    pub check: bool,
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut options = Options {
+        inputs: vec![],
+        output: None,
+        emit: Emit::Bin,
+        check: false,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" | "-h" => return Ok(Command::Help),
+            "--version" | "-V" => return Ok(Command::Version),
+            "--check" => options.check = true,
+            "-o" => match args.next() {
+                Some(path) => options.output = Some(path.clone()),
+                None => return Err("Missing path after '-o'".to_string()),
+            },
+            _ if arg.starts_with("--emit=") => {
+                options.emit = match &arg["--emit=".len()..] {
+                    "tokens" => Emit::Tokens,
+                    "ast" => Emit::Ast,
+                    "bytecode" => Emit::ByteCode,
+                    "bin" => Emit::Bin,
+                    kind => return Err(format!("Unknown emit kind '{}'", kind)),
+                }
+            }
+            _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg)),
+            _ => options.inputs.push(arg.clone()),
+        }
+    }
+
+    if options.inputs.is_empty() {
+        return Err("No input file provided".to_string());
+    }
+
+    if options.output.is_some() && options.inputs.len() > 1 {
+        return Err("'-o' cannot be used with multiple input files".to_string());
+    }
+
+    Ok(Command::Compile(options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(xs: &[&str]) -> Vec<String> {
+        xs.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn ensure_options_are_well_parsed() {
+        let command = parse_args(&args(&["-o", "fib.out", "--emit=bytecode", "fib.bug"]));
+        assert_eq!(
+            Ok(Command::Compile(Options {
+                inputs: vec!["fib.bug".to_string()],
+                output: Some("fib.out".to_string()),
+                emit: Emit::ByteCode,
+                check: false,
+            })),
+            command
+        );
+        assert_eq!(Ok(Command::Help), parse_args(&args(&["a.bug", "--help"])));
+    }
+
+    #[test]
+    fn ensure_output_path_is_rejected_with_multiple_inputs() {
+        assert!(parse_args(&args(&["-o", "out.bin", "a.bug", "b.bug"])).is_err());
+    }
+
+    #[test]
+    fn ensure_unknown_emit_kinds_are_rejected() {
+        assert!(parse_args(&args(&["--emit=exe", "a.bug"])).is_err());
+    }
+
+    #[test]
+    fn ensure_missing_inputs_are_rejected() {
+        assert!(parse_args(&args(&[])).is_err());
+    }
+}
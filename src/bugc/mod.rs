@@ -1,64 +1,154 @@
-mod analysis;
-mod ast;
-mod codegeneration;
-mod frontend;
-mod utils;
+mod cli;
 
 use std::io::Write;
 use std::process::exit;
 use std::{env, fs};
 
-use utils::read_file;
-
-use crate::analysis::Analyser;
-use crate::codegeneration::CodeGenerator;
-use frontend::{lexer::Lexer, parser::Parser};
+use bug::compiler::codegeneration::CodeGenerator;
+use bug::compiler::diagnostics::{render_all, Diagnostic};
+use bug::compiler::frontend::{lexer::Lexer, parser::Parser, SyntaxError, Token};
+use bug::container;
+use bug::disassembler::disassemble;
+use bug::utils::{default_output, read_file};
+use cli::{Command, Emit, Options};
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    if args.len() <= 1 {
-        eprintln!("[Error]: No input file provided");
-        exit(1);
-    }
-    let file_content = match read_file(&args[1]) {
-        Ok(contents) => contents,
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match cli::parse_args(&args) {
+        Ok(Command::Compile(options)) => options,
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Ok(Command::Version) => {
+            println!("bugc {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
         Err(err) => {
-            eprintln!(
-                "[Error]: Couldn't read file {} {}",
-                args[1],
-                err.to_string()
-            );
+            eprintln!("[Error]: {}\n\n{}", err, cli::USAGE);
             exit(1);
         }
     };
-    let input = file_content.to_string().chars().collect::<Vec<char>>();
-    let mut l = Lexer::new(&input);
-    let mut p = Parser::new(&mut l);
 
-    let mut ast = match p.parse() {
-        Ok(ast) => ast,
+    let mut failed = false;
+    for input in &options.inputs {
+        if compile_file(input, &options).is_err() {
+            failed = true;
+        }
+    }
+    if failed {
+        exit(1);
+    }
+}
+
+fn compile_file(path: &str, options: &Options) -> Result<(), ()> {
+    let file_content = match read_file(path) {
+        Ok(contents) => contents,
         Err(err) => {
-            println!("[Syntax Error]: {}", err);
-            exit(1);
+            eprintln!("[Error]: Couldn't read file {} {}", path, err);
+            return Err(());
         }
     };
+    let report = |diagnostics: Vec<Diagnostic>| {
+        let diagnostics = diagnostics
+            .into_iter()
+            .map(|diagnostic| diagnostic.in_file(path))
+            .collect::<Vec<Diagnostic>>();
+        eprint!("{}", render_all(&diagnostics, &file_content));
+    };
 
-    let mut analiser = Analyser::make();
-    match analiser.analyse(&mut ast) {
-        Ok(()) => {}
-        Err(errors) => {
-            for err in errors {
-                eprintln!("{}", err);
+    if !options.check {
+        match options.emit {
+            Emit::Tokens => {
+                return match emit_tokens(&file_content) {
+                    Ok(listing) => write_text(&listing, options),
+                    Err(err) => {
+                        report(vec![err.into()]);
+                        Err(())
+                    }
+                }
             }
-            exit(1);
+            Emit::Ast => {
+                let input = file_content.chars().collect::<Vec<char>>();
+                let mut l = Lexer::new(&input);
+                let mut p = Parser::new(&mut l);
+                return match p.parse() {
+                    Ok(ast) => write_text(&format!("{:#?}\n", ast), options),
+                    Err(err) => {
+                        report(vec![err.into()]);
+                        Err(())
+                    }
+                };
+            }
+            Emit::ByteCode | Emit::Bin => {}
+        }
+    }
+
+    let ast = match bug::compiler::check(&file_content) {
+        Ok(ast) => ast,
+        Err(diagnostics) => {
+            report(diagnostics);
+            return Err(());
         }
+    };
+
+    if options.check {
+        return Ok(());
     }
 
     let mut generator = CodeGenerator::make();
     let program = generator.gen(ast);
 
-    let bin = bincode::serialize(&program).unwrap();
+    match options.emit {
+        Emit::ByteCode => write_text(&disassemble(&program), options),
+        _ => {
+            let bin = container::encode(&program);
+            let output = match options.output {
+                Some(ref output) => output.clone(),
+                None => default_output(path),
+            };
+            write_file(&output, &bin)
+        }
+    }
+}
+
+fn emit_tokens(source: &str) -> Result<String, SyntaxError> {
+    let input = source.chars().collect::<Vec<char>>();
+    let mut l = Lexer::new(&input);
+    let mut lines = vec![];
+    loop {
+        let (token, span) = l.next_token()?;
+        if token == Token::Eof {
+            break;
+        }
+        lines.push((span, format!("{:?}", token)));
+    }
+    for comment in l.comments() {
+        lines.push((comment.span, format!("Comment({:?})", comment.text)));
+    }
+    lines.sort_by_key(|(span, _)| (span.line, span.colm));
+
+    Ok(lines
+        .into_iter()
+        .map(|(span, token)| format!("{:<8}{}\n", span.to_string(), token))
+        .collect())
+}
+
+fn write_text(text: &str, options: &Options) -> Result<(), ()> {
+    match options.output {
+        Some(ref output) => write_file(output, text.as_bytes()),
+        None => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
 
-    let mut file = fs::File::create("out.bin").unwrap();
-    file.write(&bin).unwrap();
+fn write_file(path: &str, contents: &[u8]) -> Result<(), ()> {
+    let written = fs::File::create(path).and_then(|mut file| file.write_all(contents));
+    if let Err(err) = written {
+        eprintln!("[Error]: Couldn't write file {} {}", path, err);
+        return Err(());
+    }
+    Ok(())
 }
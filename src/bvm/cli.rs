@@ -0,0 +1,209 @@
+pub const USAGE: &str = "\
+Usage: bvm [options] <file.bin>
+       bvm dis <file.bin>
+       bvm debug [options] <file.bin>
+
+Commands:
+  dis <file.bin>  Print the constant pool and the bytecode of every function
+  debug <file.bin>
+                  Run the program under the interactive debugger, only --fuel, --max-depth and
+                  --max-stack apply to it
+
+Options:
+  --fuel <n>      Stop the program once it has consumed <n> units of fuel, calls cost 10
+                  units, natives 20 and any other instruction 1
+  --max-depth <n> Fail with a stack overflow once calls nest deeper than <n> (default 10000)
+  --max-stack <n> Fail with a stack overflow once a function holds more than <n> values on
+                  its operand stack (default 1024)
+  --trace         Print every executed instruction with the operand stack and locals
+  --trace-json <path>
+                  Write the same trace as JSON lines to <path>
+  --profile       Print the calls, instructions and time spent in every function once the
+                  program ends, and write the folded call stacks next to <file.bin>
+  --profile-out <path>
+                  Write the folded call stacks to <path> instead
+  --version       Print the virtual machine version
+  --help          Print this message";
+
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Run(RunOptions),
+    Disassemble(String),
+    Debug(RunOptions),
+    Help,
+    Version,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RunOptions {
+    pub input: String,
+    pub fuel: Option<u64>,
+    pub max_depth: Option<usize>,
+    pub max_stack: Option<usize>,
+    pub trace: bool,
+    pub trace_json: Option<String>,
+    pub profile: bool,
+    pub profile_out: Option<String>,
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut is_dis = false;
+    let mut is_debug = false;
+    let mut inputs: Vec<String> = vec![];
+    let mut fuel = None;
+    let mut max_depth = None;
+    let mut max_stack = None;
+    let mut trace = false;
+    let mut trace_json = None;
+    let mut profile = false;
+    let mut profile_out = None;
+
+    let mut args = args.iter().enumerate();
+    while let Some((index, arg)) = args.next() {
+        match arg.as_str() {
+            "--help" | "-h" => return Ok(Command::Help),
+            "--version" | "-V" => return Ok(Command::Version),
+            "dis" if index == 0 => is_dis = true,
+            "debug" if index == 0 => is_debug = true,
+            "--fuel" => fuel = Some(parse_number(arg, args.next().map(|(_, x)| x))?),
+            "--max-depth" => {
+                max_depth = Some(parse_number(arg, args.next().map(|(_, x)| x))? as usize)
+            }
+            "--max-stack" => {
+                max_stack = Some(parse_number(arg, args.next().map(|(_, x)| x))? as usize)
+            }
+            "--trace" => trace = true,
+            "--trace-json" => match args.next() {
+                Some((_, path)) => trace_json = Some(path.clone()),
+                None => return Err(format!("Missing path after '{}'", arg)),
+            },
+            "--profile" => profile = true,
+            "--profile-out" => match args.next() {
+                Some((_, path)) => {
+                    profile = true;
+                    profile_out = Some(path.clone())
+                }
+                None => return Err(format!("Missing path after '{}'", arg)),
+            },
+            _ if arg.starts_with('-') => return Err(format!("Unknown option '{}'", arg)),
+            _ => inputs.push(arg.clone()),
+        }
+    }
+
+    let input = match inputs.len() {
+        0 => return Err("No input file provided".to_string()),
+        1 => inputs.pop().unwrap(),
+        _ => return Err("Only one input file is allowed".to_string()),
+    };
+
+    // subcommands reject the options they would silently ignore
+    let tracing = trace || trace_json.is_some();
+    if is_dis
+        && (fuel.is_some() || max_depth.is_some() || max_stack.is_some() || tracing || profile)
+    {
+        return Err("'dis' doesn't run the program and takes no options".to_string());
+    }
+    if is_debug && (tracing || profile) {
+        return Err("'debug' cannot be traced or profiled".to_string());
+    }
+
+    let options = RunOptions {
+        input,
+        fuel,
+        max_depth,
+        max_stack,
+        trace,
+        trace_json,
+        profile,
+        profile_out,
+    };
+    if is_dis {
+        Ok(Command::Disassemble(options.input))
+    } else if is_debug {
+        Ok(Command::Debug(options))
+    } else {
+        Ok(Command::Run(options))
+    }
+}
+
+fn parse_number(option: &str, value: Option<&String>) -> Result<u64, String> {
+    match value {
+        Some(value) => value
+            .parse::<u64>()
+            .map_err(|_| format!("Invalid number '{}' for '{}'", value, option)),
+        None => Err(format!("Missing value after '{}'", option)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(xs: &[&str]) -> Vec<String> {
+        xs.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn ensure_commands_are_well_parsed() {
+        assert_eq!(
+            Ok(Command::Run(RunOptions {
+                input: "out.bin".to_string(),
+                fuel: None,
+                max_depth: None,
+                max_stack: None,
+                trace: false,
+                trace_json: None,
+                profile: false,
+                profile_out: None,
+            })),
+            parse_args(&args(&["out.bin"]))
+        );
+        assert_eq!(
+            Ok(Command::Run(RunOptions {
+                input: "out.bin".to_string(),
+                fuel: Some(500),
+                max_depth: Some(64),
+                max_stack: None,
+                trace: true,
+                trace_json: Some("trace.jsonl".to_string()),
+                profile: true,
+                profile_out: Some("out.folded".to_string()),
+            })),
+            parse_args(&args(&[
+                "--fuel",
+                "500",
+                "out.bin",
+                "--max-depth",
+                "64",
+                "--trace",
+                "--trace-json",
+                "trace.jsonl",
+                "--profile-out",
+                "out.folded"
+            ]))
+        );
+        assert!(parse_args(&args(&["out.bin", "--fuel", "lots"])).is_err());
+        assert!(parse_args(&args(&["out.bin", "--fuel"])).is_err());
+        assert_eq!(
+            Ok(Command::Disassemble("out.bin".to_string())),
+            parse_args(&args(&["dis", "out.bin"]))
+        );
+        assert!(parse_args(&args(&["dis"])).is_err());
+        assert!(matches!(
+            parse_args(&args(&["debug", "out.bin"])),
+            Ok(Command::Debug(RunOptions { ref input, .. })) if input == "out.bin"
+        ));
+    }
+
+    #[test]
+    fn ensure_options_are_rejected_by_commands_ignoring_them() {
+        assert!(parse_args(&args(&["dis", "--fuel", "10", "out.bin"])).is_err());
+        assert!(parse_args(&args(&["dis", "out.bin", "--trace"])).is_err());
+        assert!(parse_args(&args(&["debug", "--trace", "out.bin"])).is_err());
+        assert!(parse_args(&args(&["debug", "--profile", "out.bin"])).is_err());
+        assert!(matches!(
+            parse_args(&args(&["debug", "--fuel", "10", "out.bin"])),
+            Ok(Command::Debug(RunOptions { fuel: Some(10), .. }))
+        ));
+    }
+}
@@ -0,0 +1,341 @@
+use std::io::{self, BufRead, Write};
+
+use bug::vm::{Runtime, Vm, VmError};
+use bug::{Object, Program};
+
+pub const HELP: &str = "\
+Commands:
+  break <fn>[:<pc>]  Stop before the instruction at <pc> (default 0) of <fn> (b)
+  break <line>       Stop before the instructions generated from source <line>
+  delete <n>         Remove breakpoint <n> (d)
+  breakpoints        List the breakpoints (bl)
+  continue           Run until a breakpoint or the end of the program (c)
+  step               Execute one instruction (s)
+  next               Execute one instruction, running calls to completion (n)
+  finish             Run until the current function returns (fin)
+  stack              Print the operand stack, top last
+  locals             Print the locals
+  backtrace          Print the active calls, innermost first (bt)
+  list               Print the instructions around the current one (l)
+  help               Print this message (h)
+  quit               Leave the debugger (q)
+An empty line repeats the last command.";
+
+/// Lines of code printed on each side of the current instruction by `list`
+const LIST_CONTEXT: usize = 4;
+
+struct Breakpoint {
+    id: usize,
+    spec: String,
+    /// Function name and offset of every instruction the breakpoint stops at
+    locations: Vec<(String, usize)>,
+}
+
+pub struct Debugger<'a> {
+    program: &'a Program,
+    runtime: Runtime<'a>,
+    breakpoints: Vec<Breakpoint>,
+    next_breakpoint_id: usize,
+    finished: bool,
+    last_command: String,
+}
+
+impl<'a> Debugger<'a> {
+    pub fn make(vm: &'a Vm, program: &'a Program) -> Result<Self, VmError> {
+        Ok(Self {
+            program,
+            runtime: vm.runtime(program)?,
+            breakpoints: vec![],
+            next_breakpoint_id: 1,
+            finished: false,
+            last_command: String::new(),
+        })
+    }
+
+    /// Executes a debugger command, returns its output and whether the debugger must quit
+    pub fn execute(&mut self, input: &str) -> (String, bool) {
+        let input = match input.trim() {
+            "" => self.last_command.clone(),
+            input => input.to_string(),
+        };
+        self.last_command = input.clone();
+
+        let mut words = input.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let arg = words.next();
+        let output = match command {
+            "" => String::new(),
+            "b" | "break" => self.add_breakpoint(arg),
+            "d" | "delete" => self.delete_breakpoint(arg),
+            "bl" | "breakpoints" => self.list_breakpoints(),
+            "c" | "continue" => self.resume(|_| false),
+            "s" | "step" => self.resume(|_| true),
+            "n" | "next" => {
+                let depth = self.runtime.depth();
+                self.resume(move |runtime| runtime.depth() <= depth)
+            }
+            "fin" | "finish" => {
+                let depth = self.runtime.depth();
+                self.resume(move |runtime| runtime.depth() < depth)
+            }
+            "stack" => render_objects(self.runtime.stack()),
+            "locals" => render_objects(self.runtime.locals()),
+            "bt" | "backtrace" => self
+                .runtime
+                .backtrace()
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| format!("#{} {}", index, entry))
+                .collect::<Vec<String>>()
+                .join("\n"),
+            "l" | "list" => self.list(),
+            "h" | "help" => HELP.to_string(),
+            "q" | "quit" => return (String::new(), true),
+            _ => format!("Unknown command '{}', try 'help'", command),
+        };
+        (output, false)
+    }
+
+    /// Where the program is stopped: function, pc, source line and the next instruction
+    pub fn location(&self) -> String {
+        let line = match self.runtime.line() {
+            Some(line) => format!(" (line {})", line),
+            None => String::new(),
+        };
+        let instr = match self.runtime.next_instr() {
+            Some(instr) => instr.to_string(),
+            None => "<end of code>".to_string(),
+        };
+        format!(
+            "{}:{}{}  {}",
+            self.runtime.function(),
+            self.runtime.pc(),
+            line,
+            instr
+        )
+    }
+
+    /// Executes instructions until `should_stop` holds after one of them, a breakpoint is reached
+    /// or the program ends
+    fn resume(&mut self, should_stop: impl Fn(&Runtime) -> bool) -> String {
+        if self.finished {
+            return "The program is not running".to_string();
+        }
+        loop {
+            match self.runtime.step() {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.finished = true;
+                    return "Program finished".to_string();
+                }
+                Err(err) => {
+                    self.finished = true;
+                    return format!("Runtime error: {}", err);
+                }
+            }
+            if let Some(id) = self.reached_breakpoint() {
+                return format!("Breakpoint {}, {}", id, self.location());
+            }
+            if should_stop(&self.runtime) {
+                return self.location();
+            }
+        }
+    }
+
+    fn reached_breakpoint(&self) -> Option<usize> {
+        let function = self.runtime.function();
+        let pc = self.runtime.pc();
+        self.breakpoints
+            .iter()
+            .find(|breakpoint| {
+                breakpoint
+                    .locations
+                    .iter()
+                    .any(|(name, offset)| name == function && *offset == pc)
+            })
+            .map(|breakpoint| breakpoint.id)
+    }
+
+    fn add_breakpoint(&mut self, spec: Option<&str>) -> String {
+        let spec = match spec {
+            Some(spec) => spec,
+            None => return "Usage: break <fn>[:<pc>] or break <line>".to_string(),
+        };
+
+        let locations = if let Ok(line) = spec.parse::<usize>() {
+            let locations = self
+                .program
+                .fns
+                .iter()
+                .flat_map(|defined_fn| {
+                    defined_fn
+                        .lines
+                        .offsets_of(line)
+                        .into_iter()
+                        .map(|pc| (defined_fn.name.clone(), pc))
+                })
+                .collect::<Vec<(String, usize)>>();
+            if locations.is_empty() {
+                return format!("No code at line {}", line);
+            }
+            locations
+        } else {
+            let (name, pc) = match spec.split_once(':') {
+                Some((name, pc)) => match pc.parse::<usize>() {
+                    Ok(pc) => (name, pc),
+                    Err(_) => return format!("Invalid pc '{}'", pc),
+                },
+                None => (spec, 0),
+            };
+            let defined_fn = match self.program.fn_index(name) {
+                Some(index) => &self.program.fns[index],
+                None => return format!("Unknown function '{}'", name),
+            };
+            if pc >= defined_fn.code.get_pos() {
+                return format!("Function '{}' has no instruction at pc {}", name, pc);
+            }
+            vec![(name.to_string(), pc)]
+        };
+
+        let breakpoint = Breakpoint {
+            id: self.next_breakpoint_id,
+            spec: spec.to_string(),
+            locations,
+        };
+        self.next_breakpoint_id += 1;
+        let output = format!("Breakpoint {}", render_breakpoint(&breakpoint));
+        self.breakpoints.push(breakpoint);
+        output
+    }
+
+    fn delete_breakpoint(&mut self, id: Option<&str>) -> String {
+        let id = match id.map(|id| id.parse::<usize>()) {
+            Some(Ok(id)) => id,
+            _ => return "Usage: delete <n>".to_string(),
+        };
+        match self
+            .breakpoints
+            .iter()
+            .position(|breakpoint| breakpoint.id == id)
+        {
+            Some(index) => {
+                self.breakpoints.remove(index);
+                format!("Deleted breakpoint {}", id)
+            }
+            None => format!("No breakpoint {}", id),
+        }
+    }
+
+    fn list_breakpoints(&self) -> String {
+        if self.breakpoints.is_empty() {
+            return "No breakpoints".to_string();
+        }
+        self.breakpoints
+            .iter()
+            .map(render_breakpoint)
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn list(&self) -> String {
+        let defined_fn = &self.program.fns[self.program.fn_index(self.runtime.function()).unwrap()];
+        let pc = self.runtime.pc();
+        defined_fn
+            .code
+            .iter()
+            .enumerate()
+            .skip(pc.saturating_sub(LIST_CONTEXT))
+            .take(LIST_CONTEXT * 2 + 1)
+            .map(|(offset, instr)| {
+                let marker = if offset == pc { "=>" } else { "  " };
+                format!("{} {:>4}  {}", marker, offset, instr)
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+fn render_breakpoint(breakpoint: &Breakpoint) -> String {
+    let locations = breakpoint
+        .locations
+        .iter()
+        .map(|(name, pc)| format!("{}:{}", name, pc))
+        .collect::<Vec<String>>()
+        .join(", ");
+    format!("{} at {} ({})", breakpoint.id, breakpoint.spec, locations)
+}
+
+fn render_objects(objects: &[Object]) -> String {
+    let objects = objects
+        .iter()
+        .map(|object| match object {
+            Object::String(x) => format!("{:?}", x),
+            object => object.to_string(),
+        })
+        .collect::<Vec<String>>();
+    format!("[{}]", objects.join(", "))
+}
+
+/// Reads commands from stdin until `quit` or the end of input
+pub fn run(vm: &Vm, program: &Program) -> Result<(), VmError> {
+    let mut debugger = Debugger::make(vm, program)?;
+    println!(
+        "Stopped at {}, type 'help' for the commands",
+        debugger.location()
+    );
+
+    let stdin = io::stdin();
+    loop {
+        print!("(bdb) ");
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        let (output, quit) = debugger.execute(&line);
+        if !output.is_empty() {
+            println!("{}", output);
+        }
+        if quit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "\
+f add(int a, int b) int ->
+  a b + return;
+
+f main ->
+  1 2 .add .write;
+";
+
+    #[test]
+    fn ensure_breakpoints_and_stepping_work() {
+        let vm = Vm::builder().build();
+        let program = vm.compile(SOURCE).unwrap();
+        let mut debugger = Debugger::make(&vm, &program).unwrap();
+        let mut run = |command: &str| debugger.execute(command).0;
+
+        assert_eq!("Breakpoint 1 at 2 (add:0)", run("break 2"));
+        assert_eq!("Breakpoint 1, add:0 (line 2)  [iload] 0", run("continue"));
+        assert_eq!("#0 add (pc 0, line 2)\n#1 main (pc 2, line 5)", run("bt"));
+        assert_eq!("[1, 2]", run("locals"));
+        assert_eq!("add:1 (line 2)  [iload] 1", run("step"));
+        assert_eq!("[1]", run("stack"));
+        assert_eq!("main:3 (line 5)  [invokenative] 0", run("finish"));
+        assert_eq!("[3]", run("stack"));
+        assert_eq!("main:4 (line 5)  [return]", run("next"));
+        assert_eq!("Program finished", run(""));
+        assert_eq!("The program is not running", run("step"));
+        assert_eq!("Unknown function 'sub'", run("break sub"));
+        assert_eq!("Deleted breakpoint 1", run("delete 1"));
+        assert_eq!("No breakpoints", run("breakpoints"));
+    }
+}
@@ -1,37 +1,144 @@
-mod core;
-mod frame;
-mod stack;
+mod cli;
+mod debugger;
+mod profile;
+mod trace;
 
 use std::env;
+use std::path::Path;
 use std::process::exit;
 
+use bug::container;
+use bug::disassembler::disassemble;
 use bug::utils::read_file_bytes;
+use bug::vm::{Vm, VmError};
 use bug::Program;
-use core::Runtime;
+use cli::{Command, RunOptions};
+use profile::Profiler;
+use trace::Tracer;
+
+fn build_vm(options: &RunOptions) -> Vm {
+    let mut builder = Vm::builder();
+    if let Some(fuel) = options.fuel {
+        builder = builder.fuel(fuel);
+    }
+    if let Some(max_depth) = options.max_depth {
+        builder = builder.max_depth(max_depth);
+    }
+    if let Some(max_stack) = options.max_stack {
+        builder = builder.max_stack(max_stack);
+    }
+    builder.build()
+}
+
+fn load_verified_program(vm: &Vm, input: &str) -> Program {
+    let program = load_program(input);
+    if let Err(err) = vm.verify(&program) {
+        eprintln!("[Error]: Rejected {}: {}", input, err);
+        exit(1);
+    }
+    program
+}
 
 fn main() {
-    let cli_args: Vec<String> = env::args().collect();
+    let cli_args: Vec<String> = env::args().skip(1).collect();
+
+    match cli::parse_args(&cli_args) {
+        Ok(Command::Run(options)) => {
+            let vm = build_vm(&options);
+            let program = load_verified_program(&vm, &options.input);
+            if let Err(err) = run(&vm, &program, &options) {
+                eprintln!("[Error]: Runtime error: {}", err);
+                exit(1);
+            }
+        }
+        Ok(Command::Debug(options)) => {
+            let vm = build_vm(&options);
+            let program = load_verified_program(&vm, &options.input);
+            if let Err(err) = debugger::run(&vm, &program) {
+                eprintln!("[Error]: Runtime error: {}", err);
+                exit(1);
+            }
+        }
+        Ok(Command::Disassemble(input)) => print!("{}", disassemble(&load_program(&input))),
+        Ok(Command::Help) => println!("{}", cli::USAGE),
+        Ok(Command::Version) => println!("bvm {}", env!("CARGO_PKG_VERSION")),
+        Err(err) => {
+            eprintln!("[Error]: {}\n\n{}", err, cli::USAGE);
+            exit(1);
+        }
+    }
+}
 
-    if cli_args.len() <= 1 {
-        eprintln!("[Error]: No input file provided");
+fn run(vm: &Vm, program: &Program, options: &RunOptions) -> Result<(), VmError> {
+    let tracing = options.trace || options.trace_json.is_some();
+    if !tracing && !options.profile {
+        return vm.run(program);
+    }
+
+    let mut tracer = match Tracer::make(options.trace, options.trace_json.as_deref()) {
+        Ok(tracer) => tracer,
+        Err(err) => {
+            eprintln!("[Error]: Couldn't create trace file: {}", err);
+            exit(1);
+        }
+    };
+    let mut runtime = vm.runtime(program)?;
+    let mut profiler = match options.profile {
+        true => Some(Profiler::make(program, &runtime)),
+        false => None,
+    };
+    let result = loop {
+        if tracing {
+            if let Err(err) = tracer.record(&runtime) {
+                eprintln!("[Error]: Couldn't write trace: {}", err);
+                exit(1);
+            }
+        }
+        let step = match profiler {
+            Some(ref mut profiler) => profiler.step(&mut runtime),
+            None => runtime.step(),
+        };
+        match step {
+            Ok(true) => continue,
+            Ok(false) => break Ok(()),
+            Err(err) => break Err(err),
+        }
+    };
+    if let Err(err) = tracer.finish() {
+        eprintln!("[Error]: Couldn't write trace: {}", err);
         exit(1);
     }
+    if let Some(profiler) = profiler {
+        eprintln!("{}", profiler.report());
+        let path = match options.profile_out {
+            Some(ref path) => path.clone(),
+            None => Path::new(&options.input)
+                .with_extension("folded")
+                .to_string_lossy()
+                .to_string(),
+        };
+        if let Err(err) = profiler.write_folded_stacks(&path) {
+            eprintln!("[Error]: Couldn't write {}: {}", path, err);
+            exit(1);
+        }
+    }
+    result
+}
 
-    let contents = match read_file_bytes(&cli_args[1]) {
+fn load_program(path: &str) -> Program {
+    let contents = match read_file_bytes(path) {
         Ok(xs) => xs,
         Err(err) => {
-            eprintln!("{:#?}", err);
+            eprintln!("[Error]: Couldn't read file {} {}", path, err);
             exit(1);
         }
     };
 
-    let program: Program = match bincode::deserialize(&contents) {
+    match container::decode(&contents) {
         Ok(program) => program,
         Err(err) => {
-            eprintln!("[Error]: Couldn't deserialize program: {:#?}\n", err);
+            eprintln!("[Error]: Couldn't load {}: {}", path, err);
             exit(1);
         }
-    };
-
-    Runtime::run(program);
+    }
 }
@@ -0,0 +1,213 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::time::{Duration, Instant};
+
+use bug::bytecode::Opcode;
+use bug::vm::{Runtime, VmError};
+use bug::Program;
+
+#[derive(Default, Clone)]
+struct FnStats {
+    calls: u64,
+    /// Instructions executed by the function itself, callees excluded
+    instructions: u64,
+    /// Wall time spent in the function itself, callees excluded
+    self_time: Duration,
+}
+
+/// Counts the instructions and wall time spent in every function of a program while it runs, along
+/// with the call stacks they were spent under
+pub struct Profiler<'a> {
+    program: &'a Program,
+    fns: Vec<FnStats>,
+    native_calls: Vec<u64>,
+    /// Indices of the functions being executed, innermost last
+    stack: Vec<usize>,
+    /// Instructions executed under each call stack, keyed like in the folded stacks format
+    folded: HashMap<String, u64>,
+    /// Instructions executed since the call stack last changed
+    pending: u64,
+    last_switch: Instant,
+    started: Instant,
+}
+
+impl<'a> Profiler<'a> {
+    pub fn make(program: &'a Program, runtime: &Runtime<'a>) -> Self {
+        let mut fns = vec![FnStats::default(); program.fns.len()];
+        let entry = program.fn_index(runtime.function()).unwrap();
+        fns[entry].calls = 1;
+        let now = Instant::now();
+        Self {
+            program,
+            fns,
+            native_calls: vec![0; program.natives.len()],
+            stack: vec![entry],
+            folded: HashMap::new(),
+            pending: 0,
+            last_switch: now,
+            started: now,
+        }
+    }
+
+    /// Executes the next instruction of `runtime` accounting for it
+    pub fn step(&mut self, runtime: &mut Runtime<'a>) -> Result<bool, VmError> {
+        let instr = runtime.next_instr();
+        let result = runtime.step();
+        self.pending += 1;
+        if !matches!(result, Ok(true)) {
+            self.switch();
+            return result;
+        }
+
+        match instr {
+            Some(Opcode::Invoke(callee)) => {
+                self.switch();
+                self.stack.push(*callee);
+                self.fns[*callee].calls += 1;
+            }
+            Some(Opcode::TailInvoke(callee)) => {
+                if self.stack.last() != Some(callee) {
+                    self.switch();
+                    *self.stack.last_mut().unwrap() = *callee;
+                }
+                self.fns[*callee].calls += 1;
+            }
+            Some(Opcode::InvokeNative(callee)) => self.native_calls[*callee] += 1,
+            Some(Opcode::Return) | Some(Opcode::ReturnTop) => {
+                self.switch();
+                self.stack.pop();
+            }
+            _ => {}
+        }
+        result
+    }
+
+    /// Accounts the instructions and time since the last change to the current call stack
+    fn switch(&mut self) {
+        let now = Instant::now();
+        let current = match self.stack.last() {
+            Some(current) => *current,
+            None => return,
+        };
+        self.fns[current].self_time += now - self.last_switch;
+        self.last_switch = now;
+        if self.pending == 0 {
+            return;
+        }
+        self.fns[current].instructions += self.pending;
+        let key = self
+            .stack
+            .iter()
+            .map(|index| self.program.fns[*index].name.as_str())
+            .collect::<Vec<&str>>()
+            .join(";");
+        *self.folded.entry(key).or_insert(0) += self.pending;
+        self.pending = 0;
+    }
+
+    /// A table of the functions sorted by the instructions they executed, hottest first
+    pub fn report(&self) -> String {
+        let total = self.fns.iter().map(|x| x.instructions).sum::<u64>();
+        let mut output = format!(
+            "[profile] {} instructions in {:.3}ms\n{:<20} {:>10} {:>14} {:>8} {:>12}",
+            total,
+            millis(self.started.elapsed()),
+            "function",
+            "calls",
+            "instructions",
+            "%",
+            "self time"
+        );
+
+        let mut fns = self
+            .fns
+            .iter()
+            .enumerate()
+            .collect::<Vec<(usize, &FnStats)>>();
+        fns.sort_by_key(|(_, stats)| Reverse(stats.instructions));
+        for (index, stats) in fns {
+            if stats.calls == 0 {
+                continue;
+            }
+            let percent = match total {
+                0 => 0.0,
+                total => stats.instructions as f64 * 100.0 / total as f64,
+            };
+            output.push_str(&format!(
+                "\n{:<20} {:>10} {:>14} {:>7.1}% {:>10.3}ms",
+                self.program.fns[index].name,
+                stats.calls,
+                stats.instructions,
+                percent,
+                millis(stats.self_time)
+            ));
+        }
+        for (index, calls) in self.native_calls.iter().enumerate() {
+            if *calls > 0 {
+                let name = format!("{} (native)", self.program.natives[index]);
+                output.push_str(&format!("\n{:<20} {:>10}", name, calls));
+            }
+        }
+        output
+    }
+
+    /// One `outer;inner count` line per call stack, as consumed by flamegraph tools
+    pub fn folded_stacks(&self) -> Vec<String> {
+        let mut lines = self
+            .folded
+            .iter()
+            .map(|(stack, count)| format!("{} {}", stack, count))
+            .collect::<Vec<String>>();
+        lines.sort();
+        lines
+    }
+
+    pub fn write_folded_stacks(&self, path: &str) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        for line in self.folded_stacks() {
+            writeln!(file, "{}", line)?;
+        }
+        file.flush()
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bug::vm::Vm;
+    use bug::{FnPrototype, Type};
+
+    #[test]
+    fn ensure_instructions_are_attributed_to_call_stacks() {
+        let vm = Vm::builder()
+            .native(
+                "ignore",
+                FnPrototype::make(vec![Type::Integer], Type::Void),
+                |_| None,
+            )
+            .build();
+        let program = vm
+            .compile(
+                "\
+f inc(int x) int -> x 1 + return;
+
+f main ->
+  1 .inc .inc .ignore;
+",
+            )
+            .unwrap();
+        let mut runtime = vm.runtime(&program).unwrap();
+        let mut profiler = Profiler::make(&program, &runtime);
+        while profiler.step(&mut runtime).unwrap() {}
+
+        assert_eq!(vec!["main 5", "main;inc 8"], profiler.folded_stacks());
+        assert_eq!(2, profiler.fns[program.fn_index("inc").unwrap()].calls);
+        assert_eq!(vec![1], profiler.native_calls);
+    }
+}
@@ -0,0 +1,119 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use bug::vm::Runtime;
+use bug::Object;
+use serde_json::{json, Value};
+
+/// Records the state of the runtime before each instruction, as text on stderr and/or as JSON
+/// lines to a file
+pub struct Tracer {
+    text: bool,
+    json: Option<BufWriter<File>>,
+}
+
+impl Tracer {
+    pub fn make(text: bool, json_path: Option<&str>) -> io::Result<Self> {
+        let json = match json_path {
+            Some(path) => Some(BufWriter::new(File::create(path)?)),
+            None => None,
+        };
+        Ok(Self { text, json })
+    }
+
+    pub fn record(&mut self, runtime: &Runtime) -> io::Result<()> {
+        if self.text {
+            eprintln!("{}", render_text(runtime));
+        }
+        if let Some(ref mut json) = self.json {
+            writeln!(json, "{}", render_json(runtime))?;
+        }
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        match self.json {
+            Some(ref mut json) => json.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn render_instr(runtime: &Runtime) -> String {
+    match runtime.next_instr() {
+        Some(instr) => instr.to_string(),
+        None => "<end of code>".to_string(),
+    }
+}
+
+fn render_text(runtime: &Runtime) -> String {
+    let objects = |objects: &[Object]| {
+        objects
+            .iter()
+            .map(|object| match object {
+                Object::String(x) => format!("{:?}", x),
+                object => object.to_string(),
+            })
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    format!(
+        "[trace] {:<12} {:>4}  {:<20} stack: [{}] locals: [{}]",
+        runtime.function(),
+        runtime.pc(),
+        render_instr(runtime),
+        objects(runtime.stack()),
+        objects(runtime.locals())
+    )
+}
+
+fn render_json(runtime: &Runtime) -> String {
+    let objects = |objects: &[Object]| {
+        objects
+            .iter()
+            .map(|object| match object {
+                Object::Int(x) => json!(x),
+                Object::String(x) => json!(x),
+                Object::Boolean(x) => json!(x),
+                Object::Float(x) if x.is_finite() => json!(x),
+                // JSON has no literal for infinities and NaN
+                Object::Float(_) => json!(object.to_string()),
+            })
+            .collect::<Vec<Value>>()
+    };
+    json!({
+        "function": runtime.function(),
+        "pc": runtime.pc(),
+        "instr": render_instr(runtime),
+        "stack": objects(runtime.stack()),
+        "locals": objects(runtime.locals()),
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bug::vm::Vm;
+
+    #[test]
+    fn ensure_state_is_rendered_before_each_instruction() {
+        let vm = Vm::builder().build();
+        let program = vm
+            .compile("f main -> int x 2 = \"ab\" x .write .write;")
+            .unwrap();
+        let mut runtime = vm.runtime(&program).unwrap();
+        for _ in 0..3 {
+            runtime.step().unwrap();
+        }
+
+        assert_eq!(
+            "{\"function\":\"main\",\"instr\":\"[iload] 0\",\"locals\":[2],\"pc\":3,\"stack\":[\"ab\"]}",
+            render_json(&runtime)
+        );
+        assert_eq!(
+            "[trace] main            3  [iload] 0            stack: [\"ab\"] locals: [2]",
+            render_text(&runtime)
+        );
+    }
+}
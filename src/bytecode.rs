@@ -23,19 +23,94 @@ pub enum Opcode {
     Nop,
     /// Add two ints on top of the stack and push the result
     IAdd,
+    /// Substract two ints on top of the stack and push the result
+    ISub,
     /// Multiply two ints on top of the stack and push the result
     IMul,
-    /// Substract two ints on top of the stack and push the result
+    /// Divide two ints on top of the stack and push the result
     IDiv,
+    /// Divide two ints on top of the stack and push the remainder
+    IRem,
     /// Return from a frame (block)
     Return,
     /// Returns the value on the top of the current stack
     ReturnTop,
-    /// Will make a function call by provided name
-    Invoke(String),
+    /// Will make a call to the defined function at provided index
+    Invoke(usize),
+    /// Will make a call to the defined function at provided index reusing the current frame, only
+    /// emitted when the call is the last thing done by the caller
+    TailInvoke(usize),
+    /// Will make a call to the native function at provided index of the program's natives
+    InvokeNative(usize),
     /// Will compare the two ints on top of stack and set the bflag register to true if the first
     /// is grather than the second
     ICmpGT,
+    /// Will compare the two ints on top of stack and push true if the first is grather than or
+    /// equal to the second
+    ICmpGE,
+    /// Will compare the two ints on top of stack and push true if the first is less than the
+    /// second
+    ICmpLT,
+    /// Will compare the two ints on top of stack and push true if the first is less than or equal
+    /// to the second
+    ICmpLE,
+    /// Will compare the two ints on top of stack and push true if they are equal
+    ICmpEQ,
+    /// Will compare the two ints on top of stack and push true if they are not equal
+    ICmpNE,
+    /// Will add two floats on top of the stack and push the result
+    FAdd,
+    /// Will substract two floats on top of the stack and push the result
+    FSub,
+    /// Will multiply two floats on top of the stack and push the result
+    FMul,
+    /// Will divide two floats on top of the stack and push the result, dividing by zero gives an
+    /// infinity or NaN as IEEE 754 defines
+    FDiv,
+    /// Will compare the two floats on top of stack and push true if the first is grather than the
+    /// second
+    FCmpGT,
+    /// Will compare the two floats on top of stack and push true if the first is grather than or
+    /// equal to the second
+    FCmpGE,
+    /// Will compare the two floats on top of stack and push true if the first is less than the
+    /// second
+    FCmpLT,
+    /// Will compare the two floats on top of stack and push true if the first is less than or
+    /// equal to the second
+    FCmpLE,
+    /// Will compare the two floats on top of stack and push true if they are equal
+    FCmpEQ,
+    /// Will compare the two floats on top of stack and push true if they are not equal
+    FCmpNE,
+    /// Will convert the int on top of stack to a float
+    I2F,
+    /// Will convert the float on top of stack to an int truncating toward zero, fails if it is
+    /// NaN or out of the int range
+    F2I,
+    /// Will compare the two values (bool or str) on top of stack and push true if they are equal
+    CmpEQ,
+    /// Will compare the two values (bool or str) on top of stack and push true if they are not
+    /// equal
+    CmpNE,
+    /// Will push the logical conjunction of the two bools on top of stack, both operands are
+    /// always evaluated
+    And,
+    /// Will push the logical disjunction of the two bools on top of stack, both operands are
+    /// always evaluated
+    Or,
+    /// Will negate the bool on top of stack
+    Not,
+    /// Will duplicate the value on top of stack ( a -- a a )
+    Dup,
+    /// Will discard the value on top of stack ( a -- )
+    Drop,
+    /// Will exchange the two values on top of stack ( a b -- b a )
+    Swap,
+    /// Will push a copy of the second value on stack ( a b -- a b a )
+    Over,
+    /// Will rotate the third value on stack to the top ( a b c -- b c a )
+    Rot,
     // Will jump to the provided offset
     Jump(usize),
     /// Will jump to the provided offset if the top of stack is a bool value false
@@ -79,7 +154,7 @@ impl ByteCodeStream {
         if offset >= self.code.len() {
             return None;
         }
-        return Some(&self.code[offset]);
+        Some(&self.code[offset])
     }
 
     pub fn get_pos(&self) -> usize {
@@ -89,6 +164,10 @@ impl ByteCodeStream {
     pub fn clear(&mut self) {
         self.code.clear()
     }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Opcode> {
+        self.code.iter()
+    }
 }
 
 impl fmt::Display for Opcode {
@@ -98,14 +177,45 @@ impl fmt::Display for Opcode {
             Self::IAdd => write!(f, "[iadd]"),
             Self::Return => write!(f, "[return]"),
             Self::ReturnTop => write!(f, "[ireturn]"),
-            Self::Invoke(name) => write!(f, "[invoke] {}", name),
+            Self::Invoke(index) => write!(f, "[invoke] {}", index),
+            Self::TailInvoke(index) => write!(f, "[tailinvoke] {}", index),
+            Self::InvokeNative(index) => write!(f, "[invokenative] {}", index),
             Self::LLoad(index) => write!(f, "[iload] {}", index),
             Self::LStore(index) => write!(f, "[istore] {}", index),
             Self::Push(iconst) => write!(f, "[bipush] {}", iconst),
+            Self::ISub => write!(f, "[isub]"),
             Self::IMul => write!(f, "[imul]"),
             Self::IDiv => write!(f, "[idiv]"),
+            Self::IRem => write!(f, "[irem]"),
             Self::Ldc(usize) => write!(f, "[ldc] {}", usize),
             Self::ICmpGT => write!(f, "[icmpgt]"),
+            Self::ICmpGE => write!(f, "[icmpge]"),
+            Self::ICmpLT => write!(f, "[icmplt]"),
+            Self::ICmpLE => write!(f, "[icmple]"),
+            Self::ICmpEQ => write!(f, "[icmpeq]"),
+            Self::ICmpNE => write!(f, "[icmpne]"),
+            Self::FAdd => write!(f, "[fadd]"),
+            Self::FSub => write!(f, "[fsub]"),
+            Self::FMul => write!(f, "[fmul]"),
+            Self::FDiv => write!(f, "[fdiv]"),
+            Self::FCmpGT => write!(f, "[fcmpgt]"),
+            Self::FCmpGE => write!(f, "[fcmpge]"),
+            Self::FCmpLT => write!(f, "[fcmplt]"),
+            Self::FCmpLE => write!(f, "[fcmple]"),
+            Self::FCmpEQ => write!(f, "[fcmpeq]"),
+            Self::FCmpNE => write!(f, "[fcmpne]"),
+            Self::I2F => write!(f, "[i2f]"),
+            Self::F2I => write!(f, "[f2i]"),
+            Self::CmpEQ => write!(f, "[cmpeq]"),
+            Self::CmpNE => write!(f, "[cmpne]"),
+            Self::And => write!(f, "[and]"),
+            Self::Or => write!(f, "[or]"),
+            Self::Not => write!(f, "[not]"),
+            Self::Dup => write!(f, "[dup]"),
+            Self::Drop => write!(f, "[drop]"),
+            Self::Swap => write!(f, "[swap]"),
+            Self::Over => write!(f, "[over]"),
+            Self::Rot => write!(f, "[rot]"),
             Self::JumpIfFalse(usize) => write!(f, "[jumpiffalse] {usize}"),
             Self::Jump(offset) => write!(f, "[jump] {offset}"),
         }
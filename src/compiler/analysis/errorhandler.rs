@@ -1,5 +1,8 @@
 use core::fmt;
 
+use crate::compiler::diagnostics::Diagnostic;
+use crate::compiler::span::Span;
+
 #[derive(Debug, Clone)]
 enum AnalyserErrorKind {
     Type,
@@ -23,41 +26,55 @@ impl fmt::Display for AnalyserErrorKind {
 pub struct AnalyserError {
     kind: AnalyserErrorKind,
     msg: String,
+    span: Option<Span>,
 }
 
 impl AnalyserError {
-    pub fn type_error(msg: String) -> Self {
+    pub fn type_error(msg: String, span: Option<Span>) -> Self {
         Self {
             kind: AnalyserErrorKind::Type,
             msg,
+            span,
         }
     }
 
-    pub fn name_error(msg: String) -> Self {
+    pub fn name_error(msg: String, span: Option<Span>) -> Self {
         Self {
             kind: AnalyserErrorKind::Name,
             msg,
+            span,
         }
     }
 
-    pub fn arg_error(msg: String) -> Self {
+    pub fn arg_error(msg: String, span: Option<Span>) -> Self {
         Self {
             kind: AnalyserErrorKind::Argument,
             msg,
+            span,
         }
     }
 
-    pub fn illegal_decl(msg: String) -> Self {
+    pub fn illegal_decl(msg: String, span: Option<Span>) -> Self {
         Self {
             kind: AnalyserErrorKind::IllegalDeclaration,
             msg,
+            span,
         }
     }
 }
 
+impl From<AnalyserError> for Diagnostic {
+    fn from(err: AnalyserError) -> Self {
+        Diagnostic::make(err.kind.to_string(), err.msg, err.span)
+    }
+}
+
 impl fmt::Display for AnalyserError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: {}", self.kind, self.msg)
+        match self.span {
+            Some(span) => write!(f, "{}: {} at {}", self.kind, self.msg, span),
+            None => write!(f, "{}: {}", self.kind, self.msg),
+        }
     }
 }
 
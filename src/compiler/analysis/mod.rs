@@ -0,0 +1,995 @@
+mod errorhandler;
+mod scope;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::compiler::ast::*;
+use crate::compiler::span::Span;
+use crate::stdlib::{list_native_fns, Natives};
+use crate::{FnPrototype, Type};
+pub use errorhandler::{AnalyserError, AnalyserErrors};
+use scope::{MetaObject, Scope, ScopeType};
+
+#[derive(Debug, Clone)]
+enum MetaStackEntry {
+    Type(Type),
+    Identifier(String, Type),
+    VariableDeclaration(String, Type),
+}
+
+/// State of the analyser to go back to when a REPL line can't be executed
+pub struct Checkpoint {
+    scope: Scope,
+    metastack: Vec<MetaStackEntry>,
+}
+
+pub struct Analyser {
+    scope: Rc<RefCell<Scope>>,
+    metastack: Vec<MetaStackEntry>,
+    errors: AnalyserErrors,
+    /// Stack depth at the entry of every loop being analysed, innermost last
+    loops: Vec<usize>,
+    /// Types on the stack after each word, only kept once `record_stacks` is called
+    stacks: Option<Vec<(Span, Vec<Type>)>>,
+}
+
+impl Analyser {
+    pub fn make() -> Self {
+        Self::make_with(&list_native_fns())
+    }
+
+    /// Analyser seeing the natives of `natives` instead of the standard ones
+    pub fn make_with(natives: &Natives) -> Self {
+        Self {
+            scope: Rc::new(RefCell::new(Scope::make_global(natives))),
+            metastack: vec![],
+            errors: vec![],
+            loops: vec![],
+            stacks: None,
+        }
+    }
+
+    /// Keeps the types on the stack after every expression, declaration and assignment analysed
+    /// from now on, for editors to show them
+    pub fn record_stacks(&mut self) {
+        self.stacks = Some(vec![]);
+    }
+
+    /// Spans of the words analysed since `record_stacks` with the types on the stack after each
+    pub fn recorded_stacks(&self) -> &[(Span, Vec<Type>)] {
+        self.stacks.as_deref().unwrap_or(&[])
+    }
+
+    pub fn analyse(&mut self, ast: &mut Ast) -> Result<(), AnalyserErrors> {
+        for stmt in ast {
+            self.analyse_statement(stmt);
+        }
+        self.check_main_function();
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors.clone())
+        }
+    }
+
+    /// Analyses a line typed at the REPL, its declarations stay in the global scope and the types it
+    /// leaves on the stack carry over to the next line. A line with errors changes nothing
+    pub fn analyse_line(&mut self, ast: &mut Ast) -> Result<(), AnalyserErrors> {
+        let checkpoint = self.checkpoint();
+        for stmt in ast {
+            if let Statement::FunctionDeclaration(_) = stmt {
+                // a function body starts with an empty stack, the line's one is put back after it
+                let metastack = std::mem::take(&mut self.metastack);
+                self.analyse_statement(stmt);
+                self.metastack = metastack;
+            } else {
+                self.analyse_statement(stmt);
+            }
+        }
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        self.restore(checkpoint);
+        Err(std::mem::take(&mut self.errors))
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            scope: self.scope.borrow().clone(),
+            metastack: self.metastack.clone(),
+        }
+    }
+
+    pub fn restore(&mut self, checkpoint: Checkpoint) {
+        *self.scope.borrow_mut() = checkpoint.scope;
+        self.metastack = checkpoint.metastack;
+    }
+
+    /// Types of the values left on the stack by the lines analysed so far, top last
+    pub fn stack_types(&self) -> Vec<Type> {
+        types_of(&self.metastack)
+    }
+
+    fn check_main_function(&mut self) {
+        let main_fn = self.scope.borrow().lookup("main");
+        if main_fn.is_none() {
+            self.errors.push(AnalyserError::name_error(
+                "Missing 'main' function".to_string(),
+                None,
+            ));
+            return;
+        }
+
+        if let MetaObject::FnPrototype(fn_prototype) = main_fn.unwrap() {
+            if fn_prototype.arity != 0 || fn_prototype.return_type != Type::Void {
+                self.errors.push(AnalyserError::type_error(
+                    "'main' function cannot accept args or return some value".to_string(),
+                    None,
+                ));
+            }
+        } else {
+            self.errors.push(AnalyserError::name_error(
+                "'main' must be declared as function".to_string(),
+                None,
+            ));
+        }
+    }
+
+    fn analyse_statement(&mut self, stmt: &mut Statement) {
+        let span = stmt.span();
+        let is_word = matches!(
+            stmt,
+            Statement::Expression(_)
+                | Statement::VariableDeclaration(_)
+                | Statement::Assignment(_, _)
+        );
+        match stmt {
+            Statement::If(consequence, alternative, span) => {
+                self.analyse_if_statement(consequence, alternative, *span)
+            }
+            Statement::While(condition, body, span) => {
+                self.analyse_while_statement(condition, body, *span)
+            }
+            Statement::Break(span) => self.analyse_loop_jump("break", *span),
+            Statement::Continue(span) => self.analyse_loop_jump("continue", *span),
+            Statement::Expression(expression) => self.analyse_expression(expression),
+            Statement::FunctionDeclaration(fn_decl) => self.analyse_function_declaration(fn_decl),
+            Statement::VariableDeclaration(var_decl) => self.analyse_variable_declaration(var_decl),
+            Statement::Assignment(target, span) => self.analyse_assignment(target, *span),
+        }
+        if is_word {
+            let types = self.stack_types();
+            if let Some(ref mut stacks) = self.stacks {
+                stacks.push((span, types));
+            }
+        }
+    }
+
+    fn analyse_assignment(&mut self, target: &mut Option<AssignmentTarget>, span: Span) {
+        if self.metastack.len() < 2 {
+            self.errors.push(AnalyserError::arg_error(
+                "Assignment (=) expects two operands on stack".to_string(),
+                Some(span),
+            ));
+            return;
+        }
+
+        let rhs_type = match self.metastack.pop().unwrap() {
+            MetaStackEntry::Type(type_) => type_,
+            MetaStackEntry::Identifier(_, type_) => type_,
+            _ => {
+                self.errors.push(AnalyserError::type_error(
+                    "right side of assignment must be an expression".to_string(),
+                    Some(span),
+                ));
+                return;
+            }
+        };
+
+        let (lhs_name, lhs_type, is_loaded) = match self.metastack.pop().unwrap() {
+            MetaStackEntry::Identifier(name, type_) => (name, type_, true),
+            MetaStackEntry::VariableDeclaration(name, type_) => (name, type_, false),
+            _ => {
+                self.errors.push(AnalyserError::type_error(
+                    "Cannot assign to a non-variable".to_string(),
+                    Some(span),
+                ));
+                return;
+            }
+        };
+
+        if lhs_type != rhs_type {
+            self.errors.push(AnalyserError::type_error(
+                format!(
+                    "Cannot assign value of type '{}' to variable '{}' which has type '{}'",
+                    rhs_type, lhs_name, lhs_type
+                ),
+                Some(span),
+            ));
+            return;
+        }
+
+        *target = Some(AssignmentTarget {
+            name: lhs_name,
+            is_loaded,
+        });
+    }
+
+    fn analyse_variable_declaration(&mut self, var_decl: &mut VariableDeclaration) {
+        let span = var_decl.span;
+        if self.scope.borrow().is_declared(&var_decl.name) {
+            self.errors.push(AnalyserError::name_error(
+                format!("'{}' is already bound", var_decl.name),
+                Some(span),
+            ));
+            return;
+        }
+        self.scope.borrow_mut().declare(
+            var_decl.name.clone(),
+            MetaObject::VarType(var_decl.type_.clone()),
+        );
+        self.metastack.push(MetaStackEntry::VariableDeclaration(
+            var_decl.name.clone(),
+            var_decl.type_.clone(),
+        ));
+    }
+
+    fn analyse_if_statement(
+        &mut self,
+        consequence: &mut BlockStatement,
+        alternative: &mut Option<BlockStatement>,
+        span: Span,
+    ) {
+        if self.metastack.is_empty() {
+            self.errors.push(AnalyserError::arg_error(
+                "'if' expects boolean value on top of the stack".to_string(),
+                Some(span),
+            ));
+            return;
+        }
+
+        let provided_type = match self.metastack.pop().unwrap() {
+            MetaStackEntry::Type(type_) => type_,
+            MetaStackEntry::Identifier(_, type_) => type_,
+            _ => {
+                self.errors.push(AnalyserError::arg_error(
+                    "'if' expects boolean value on top of the stack".to_string(),
+                    Some(span),
+                ));
+                return;
+            }
+        };
+
+        if provided_type != Type::Boolean {
+            self.errors.push(AnalyserError::type_error(
+                "'if' expects boolean value on top of the stack".to_string(),
+                Some(span),
+            ));
+            return;
+        }
+
+        // whatever falls out of either block is what the code after the `if` starts from, so
+        // both ways must leave the same values, without `else` that's the stack it found
+        let entry = self.metastack.clone();
+        let consequence_exit = self.analyse_block(consequence);
+        self.metastack = entry.clone();
+        let alternative_exit = match alternative {
+            Some(alternative) => self.analyse_block(alternative),
+            None => Some(entry.clone()),
+        };
+
+        self.metastack = match (consequence_exit, alternative_exit) {
+            (Some(consequence_exit), Some(alternative_exit)) => {
+                if types_of(&consequence_exit) != types_of(&alternative_exit) {
+                    let msg = if alternative.is_some() {
+                        "'if' and 'else' blocks must leave the same values on the stack"
+                    } else {
+                        "'if' block must leave the stack as it found it"
+                    };
+                    self.errors
+                        .push(AnalyserError::type_error(msg.to_string(), Some(span)));
+                }
+                consequence_exit
+            }
+            (Some(exit), None) | (None, Some(exit)) => exit,
+            (None, None) => entry,
+        };
+    }
+
+    /// Analyses the statements of a block, giving back the stack it falls through with or `None`
+    /// when it leaves through `break`, `continue` or `return`
+    fn analyse_block(&mut self, block: &mut BlockStatement) -> Option<Vec<MetaStackEntry>> {
+        let ends_with_jump = ends_with_jump(block);
+        for stmt in block {
+            self.analyse_statement(stmt);
+        }
+        if ends_with_jump {
+            None
+        } else {
+            Some(self.metastack.clone())
+        }
+    }
+
+    fn analyse_while_statement(
+        &mut self,
+        condition: &mut BlockStatement,
+        body: &mut BlockStatement,
+        span: Span,
+    ) {
+        let entry = self.metastack.clone();
+        let depth = entry.len();
+
+        for stmt in condition {
+            self.analyse_statement(stmt);
+        }
+
+        // a bad condition is reported without giving up on the body, which still gets analysed
+        // from the stack the loop was entered with
+        match self.metastack.pop() {
+            Some(MetaStackEntry::Type(type_)) | Some(MetaStackEntry::Identifier(_, type_)) => {
+                if type_ != Type::Boolean {
+                    self.errors.push(AnalyserError::type_error(
+                        format!("'while' expects boolean condition but provided '{}'", type_),
+                        Some(span),
+                    ));
+                    self.metastack = entry.clone();
+                } else if self.metastack.len() != depth {
+                    self.errors.push(AnalyserError::type_error(
+                        "'while' condition must only push a boolean value".to_string(),
+                        Some(span),
+                    ));
+                    self.metastack = entry.clone();
+                }
+            }
+            _ => {
+                self.errors.push(AnalyserError::arg_error(
+                    "'while' expects its condition to leave a boolean value on top of the stack"
+                        .to_string(),
+                    Some(span),
+                ));
+                self.metastack = entry.clone();
+            }
+        }
+
+        let body_ends_with_jump = ends_with_jump(body);
+        self.loops.push(depth);
+        for stmt in body {
+            self.analyse_statement(stmt);
+        }
+        self.loops.pop();
+
+        if body_ends_with_jump {
+            // the jump was checked against the depth of the loop already
+        } else if self.metastack.len() > depth {
+            self.errors.push(AnalyserError::type_error(
+                "'while' body must not leave values on the stack".to_string(),
+                Some(span),
+            ));
+        } else if self.metastack.len() < depth {
+            self.errors.push(AnalyserError::type_error(
+                "'while' body must not consume values from outside of the loop".to_string(),
+                Some(span),
+            ));
+        }
+        self.metastack = entry;
+    }
+
+    fn analyse_loop_jump(&mut self, name: &str, span: Span) {
+        let depth = match self.loops.last() {
+            Some(depth) => *depth,
+            None => {
+                self.errors.push(AnalyserError::illegal_decl(
+                    format!("'{}' outside of a loop", name),
+                    Some(span),
+                ));
+                return;
+            }
+        };
+        if self.metastack.len() != depth {
+            self.errors.push(AnalyserError::type_error(
+                format!(
+                    "'{}' must leave the stack as it was when the loop started",
+                    name
+                ),
+                Some(span),
+            ));
+        }
+    }
+
+    fn analyse_function_declaration(&mut self, fn_decl: &mut FunctionDeclaration) {
+        let span = fn_decl.span;
+        self.metastack.clear();
+
+        if self.scope.borrow().type_ != ScopeType::Global {
+            self.errors.push(AnalyserError::illegal_decl(
+                "Functions must be only declared in global scope".to_string(),
+                Some(span),
+            ));
+        }
+
+        if self.scope.borrow().is_declared(&fn_decl.name) {
+            self.errors.push(AnalyserError::name_error(
+                format!("'{}' is already bound", &fn_decl.name),
+                Some(span),
+            ));
+        }
+
+        self.scope.borrow_mut().declare(
+            fn_decl.name.clone(),
+            MetaObject::FnPrototype(FnPrototype {
+                arity: fn_decl.params.len() as u8,
+                argtypes: fn_decl
+                    .params
+                    .iter()
+                    .map(|param| param.type_.clone())
+                    .collect(),
+                return_type: fn_decl.return_type.clone(),
+            }),
+        );
+
+        let global_context = Rc::clone(&self.scope);
+        let fn_context = Rc::new(RefCell::new(Scope::make(
+            ScopeType::Function,
+            fn_decl.return_type.clone(),
+            Rc::clone(&global_context),
+        )));
+        self.scope = fn_context;
+
+        for param in &fn_decl.params {
+            if self.scope.borrow().lookup(&param.name).is_some() {
+                self.errors.push(AnalyserError::name_error(
+                    format!(
+                        "Duplicated parameter name '{}' for function '{}'",
+                        param.name, fn_decl.name
+                    ),
+                    Some(param.span),
+                ));
+            }
+
+            self.scope
+                .borrow_mut()
+                .declare(param.name.clone(), MetaObject::VarType(param.type_.clone()))
+        }
+
+        for stmt in &mut fn_decl.body {
+            self.analyse_statement(stmt);
+        }
+
+        if self.metastack.is_empty() {
+            if fn_decl.return_type != Type::Void {
+                self.scope = global_context;
+                self.errors.push(AnalyserError::type_error(
+                    format!(
+                        "Missing return value for a non-void function '{}'",
+                        &fn_decl.name
+                    ),
+                    Some(span),
+                ));
+                return;
+            }
+        } else {
+            let provided_type = match self.metastack.pop().unwrap() {
+                MetaStackEntry::Type(type_) => type_,
+                MetaStackEntry::Identifier(_, type_) => type_,
+                MetaStackEntry::VariableDeclaration(_, _) => Type::Void,
+            };
+            if provided_type != fn_decl.return_type {
+                self.scope = global_context;
+                self.errors.push(AnalyserError::type_error(
+                    format!(
+                        "Function '{}' expects return type {} but provided {}",
+                        &fn_decl.name, fn_decl.return_type, provided_type
+                    ),
+                    fn_decl.body.last().map(Statement::span).or(Some(span)),
+                ));
+                return;
+            }
+        }
+
+        self.scope = global_context;
+    }
+
+    fn analyse_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Literal(literal, _) => self.analyse_literal_expression(literal),
+            Expression::FunctionCall(fn_name, span) => self.analyse_function_call(fn_name, *span),
+            Expression::BinaryOp(binop, span) => self.analyse_binop(binop, *span),
+            Expression::UnaryOp(unop, span) => self.analyse_unop(unop, *span),
+            Expression::StackOp(stackop, span) => self.analyse_stackop(stackop, *span),
+            Expression::Identifier(ident, span) => self.analyse_identifier(ident, *span),
+            Expression::Return(type_, span) => self.analyse_return_expression(type_, *span),
+        }
+    }
+
+    fn analyse_identifier(&mut self, ident: &mut String, span: Span) {
+        let object = self.scope.borrow().lookup(ident);
+        if object.is_none() {
+            self.errors.push(AnalyserError::name_error(
+                format!("'{}' is unbound", ident),
+                Some(span),
+            ));
+            return;
+        }
+        match object.unwrap() {
+            MetaObject::FnPrototype(_) => self.errors.push(AnalyserError::type_error(
+                format!("'{}' is a function, call it with '.{}'", ident, ident),
+                Some(span),
+            )),
+            MetaObject::VarType(type_) => self
+                .metastack
+                .push(MetaStackEntry::Identifier(ident.clone(), type_)),
+        }
+    }
+
+    fn analyse_binop(&mut self, binop: &mut BinaryOp, span: Span) {
+        if self.metastack.len() < 2 {
+            self.errors.push(AnalyserError::type_error(
+                format!("Missing operands for '{}' operation", binop),
+                Some(span),
+            ));
+            return;
+        }
+
+        let rhs_type = match self.metastack.pop().unwrap() {
+            MetaStackEntry::Type(type_) => type_,
+            MetaStackEntry::Identifier(_, type_) => type_,
+            _ => {
+                self.errors.push(AnalyserError::type_error(
+                    format!("Right side of '{}' operation must an expression", binop),
+                    Some(span),
+                ));
+                return;
+            }
+        };
+        let lhs_type = match self.metastack.pop().unwrap() {
+            MetaStackEntry::Type(type_) => type_,
+            MetaStackEntry::Identifier(_, type_) => type_,
+            _ => {
+                self.errors.push(AnalyserError::type_error(
+                    format!("Left side of '{}' operation must an expression", binop),
+                    Some(span),
+                ));
+                return;
+            }
+        };
+
+        if lhs_type != rhs_type {
+            self.errors.push(AnalyserError::type_error(
+                format!(
+                    "Operands of '{}' operation must be of same type, but provided '{}' and '{}'",
+                    binop, lhs_type, rhs_type
+                ),
+                Some(span),
+            ));
+        }
+
+        let result_type = match (&binop, &lhs_type) {
+            (
+                BinaryOp::Plus(_)
+                | BinaryOp::Minus(_)
+                | BinaryOp::Multiply(_)
+                | BinaryOp::Divide(_)
+                | BinaryOp::Modulo(_),
+                Type::Integer,
+            ) => Type::Integer,
+            (
+                BinaryOp::Plus(_)
+                | BinaryOp::Minus(_)
+                | BinaryOp::Multiply(_)
+                | BinaryOp::Divide(_),
+                Type::Float,
+            ) => Type::Float,
+            (
+                BinaryOp::GratherThan(_)
+                | BinaryOp::GratherOrEqual(_)
+                | BinaryOp::LessThan(_)
+                | BinaryOp::LessOrEqual(_),
+                Type::Integer | Type::Float,
+            ) => Type::Boolean,
+            (
+                BinaryOp::Equal(_) | BinaryOp::NotEqual(_),
+                Type::Integer | Type::Boolean | Type::String | Type::Float,
+            ) => Type::Boolean,
+            (BinaryOp::And(_) | BinaryOp::Or(_), Type::Boolean) => Type::Boolean,
+            _ => {
+                self.errors.push(AnalyserError::type_error(
+                    format!(
+                        "'{}' operation not supported for '{}' type",
+                        binop, lhs_type
+                    ),
+                    Some(span),
+                ));
+                return;
+            }
+        };
+
+        binop.set_operands_type(lhs_type);
+        self.metastack.push(MetaStackEntry::Type(result_type));
+    }
+
+    fn analyse_unop(&mut self, unop: &mut UnaryOp, span: Span) {
+        let operand_type = match self.metastack.pop() {
+            Some(MetaStackEntry::Type(type_)) => type_,
+            Some(MetaStackEntry::Identifier(_, type_)) => type_,
+            Some(_) => {
+                self.errors.push(AnalyserError::type_error(
+                    format!("Operand of '{}' operation must be an expression", unop),
+                    Some(span),
+                ));
+                return;
+            }
+            None => {
+                self.errors.push(AnalyserError::type_error(
+                    format!("Missing operand for '{}' operation", unop),
+                    Some(span),
+                ));
+                return;
+            }
+        };
+
+        match (&unop, &operand_type) {
+            (UnaryOp::Not, Type::Boolean) => {
+                self.metastack.push(MetaStackEntry::Type(Type::Boolean))
+            }
+            (UnaryOp::IntToFloat, Type::Integer) => {
+                self.metastack.push(MetaStackEntry::Type(Type::Float))
+            }
+            (UnaryOp::FloatToInt, Type::Float) => {
+                self.metastack.push(MetaStackEntry::Type(Type::Integer))
+            }
+            _ => self.errors.push(AnalyserError::type_error(
+                format!(
+                    "'{}' operation not supported for '{}' type",
+                    unop, operand_type
+                ),
+                Some(span),
+            )),
+        }
+    }
+
+    fn analyse_stackop(&mut self, stackop: &mut StackOp, span: Span) {
+        let depth = stackop.depth();
+        if self.metastack.len() < depth {
+            self.errors.push(AnalyserError::arg_error(
+                format!(
+                    "'{}' expects {} value(s) on the stack but found {}",
+                    stackop,
+                    depth,
+                    self.metastack.len()
+                ),
+                Some(span),
+            ));
+            return;
+        }
+
+        let top = self.metastack.len() - depth;
+        let is_value =
+            |entry: &MetaStackEntry| !matches!(entry, MetaStackEntry::VariableDeclaration(_, _));
+        if !self.metastack[top..].iter().all(is_value) {
+            self.errors.push(AnalyserError::type_error(
+                format!("Operands of '{}' must be expressions", stackop),
+                Some(span),
+            ));
+            return;
+        }
+
+        match stackop {
+            StackOp::Dup => {
+                let entry = self.metastack[top].clone();
+                self.metastack.push(entry);
+            }
+            StackOp::Drop => {
+                self.metastack.pop();
+            }
+            StackOp::Swap => self.metastack.swap(top, top + 1),
+            StackOp::Over => {
+                let entry = self.metastack[top].clone();
+                self.metastack.push(entry);
+            }
+            StackOp::Rot => self.metastack[top..].rotate_left(1),
+        }
+    }
+
+    fn analyse_literal_expression(&mut self, literal: &mut Literal) {
+        match literal {
+            Literal::Int(_) => self.metastack.push(MetaStackEntry::Type(Type::Integer)),
+            Literal::Float(_) => self.metastack.push(MetaStackEntry::Type(Type::Float)),
+            Literal::String(_) => self.metastack.push(MetaStackEntry::Type(Type::String)),
+            Literal::Boolean(_) => self.metastack.push(MetaStackEntry::Type(Type::Boolean)),
+        }
+    }
+
+    fn analyse_function_call(&mut self, fn_name: &mut String, span: Span) {
+        let func = self.scope.borrow().lookup(fn_name);
+        if func.is_none() {
+            self.errors.push(AnalyserError::name_error(
+                format!("'{}' is unbound", fn_name),
+                Some(span),
+            ));
+            return;
+        }
+        let prototype = match func.unwrap() {
+            MetaObject::FnPrototype(prototype) => prototype,
+            _ => {
+                self.errors.push(AnalyserError::type_error(
+                    format!("'{}' is not callable", fn_name),
+                    Some(span),
+                ));
+                return;
+            }
+        };
+
+        if (self.metastack.len() as u8) < prototype.arity {
+            self.errors.push(AnalyserError::arg_error(
+                format!("Missing arguments for function '{}'", fn_name),
+                Some(span),
+            ));
+            return;
+        }
+
+        let args = &self.metastack[self.metastack.len() - prototype.arity as usize..];
+        for (index, (expected_type, provided_type)) in
+            prototype.argtypes.iter().zip(args).enumerate()
+        {
+            let provided_type = match provided_type {
+                MetaStackEntry::Type(type_) => type_,
+                MetaStackEntry::Identifier(_, type_) => type_,
+                _ => {
+                    self.errors.push(AnalyserError::arg_error(
+                        "Function argument must an expression".to_string(),
+                        Some(span),
+                    ));
+                    return;
+                }
+            };
+
+            if expected_type != provided_type {
+                self.errors.push(AnalyserError::type_error(format!(
+                    "The {} parameter of '{}' function expected to be of type '{}' but provided value of type '{}'",
+                    index + 1,
+                    fn_name,
+                    expected_type,
+                    provided_type
+                ), Some(span)));
+                return;
+            }
+        }
+
+        for _ in 0..prototype.arity {
+            self.metastack.pop();
+        }
+
+        if prototype.return_type != Type::Void {
+            self.metastack
+                .push(MetaStackEntry::Type(prototype.return_type.clone()));
+        }
+    }
+
+    fn analyse_return_expression(&mut self, type_: &mut Option<Type>, span: Span) {
+        if self.scope.borrow().type_ == ScopeType::Global {
+            self.errors.push(AnalyserError::illegal_decl(
+                "'return' outside of a function".to_string(),
+                Some(span),
+            ));
+            return;
+        }
+
+        let provided_type = match self.metastack.last() {
+            Some(MetaStackEntry::Type(type_)) => type_.clone(),
+            Some(MetaStackEntry::Identifier(_, type_)) => type_.clone(),
+            Some(MetaStackEntry::VariableDeclaration(name, _)) => {
+                self.errors.push(AnalyserError::type_error(
+                    format!("Cannot return the declaration of variable '{}'", name),
+                    Some(span),
+                ));
+                return;
+            }
+            None => Type::Void,
+        };
+
+        // the function must hand over exactly the value it declares, nothing below it
+        let expected_type = self.scope.borrow().expected_type.clone();
+        if expected_type == Type::Void && !self.metastack.is_empty() {
+            self.errors.push(AnalyserError::type_error(
+                "'return' from a void function must leave the stack empty".to_string(),
+                Some(span),
+            ));
+            return;
+        }
+        if expected_type != Type::Void
+            && (self.metastack.len() != 1 || provided_type != expected_type)
+        {
+            self.errors.push(AnalyserError::type_error(
+                format!(
+                    "'return' expects exactly one value of type '{}' on the stack",
+                    expected_type
+                ),
+                Some(span),
+            ));
+            return;
+        }
+        *type_ = Some(provided_type);
+    }
+}
+
+/// Types of the values a meta stack stands for, declarations don't take a slot at runtime
+fn types_of(metastack: &[MetaStackEntry]) -> Vec<Type> {
+    metastack
+        .iter()
+        .filter_map(|entry| match entry {
+            MetaStackEntry::Type(type_) | MetaStackEntry::Identifier(_, type_) => {
+                Some(type_.clone())
+            }
+            MetaStackEntry::VariableDeclaration(_, _) => None,
+        })
+        .collect()
+}
+
+/// Whether `block` leaves through `break`, `continue` or `return` instead of falling through
+fn ends_with_jump(block: &BlockStatement) -> bool {
+    matches!(
+        block.last(),
+        Some(
+            Statement::Break(_)
+                | Statement::Continue(_)
+                | Statement::Expression(Expression::Return(_, _))
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Analyser;
+    use crate::compiler::frontend::{lexer::Lexer, parser::Parser};
+    use crate::Type;
+
+    fn analyse(input: &str) -> Result<(), String> {
+        let input = input.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut p = Parser::new(&mut l);
+        let mut ast = p.parse().map_err(|err| err.to_string())?;
+        Analyser::make()
+            .analyse(&mut ast)
+            .map_err(|errors| errors[0].to_string())
+    }
+
+    #[test]
+    fn ensure_stack_words_keep_track_of_types() {
+        assert!(analyse("f g(int x, str y) -> ; f main -> \"s\" 1 swap .g;").is_ok());
+        assert!(analyse("f g(int x, str y) -> ; f main -> 1 \"s\" swap .g;").is_err());
+        assert!(analyse("f g(int x, int y, str z) -> ; f main -> \"s\" 1 2 rot .g;").is_ok());
+        assert!(analyse("f g(int x) int -> x dup drop; f main -> 1 .g drop;").is_ok());
+    }
+
+    fn analyse_line(analyser: &mut Analyser, input: &str) -> bool {
+        let input = input.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut p = Parser::new(&mut l);
+        analyser.analyse_line(&mut p.parse().unwrap()).is_ok()
+    }
+
+    #[test]
+    fn ensure_lines_keep_declarations_and_stack() {
+        let mut analyser = Analyser::make();
+        assert!(analyse_line(&mut analyser, "int x 1 = \"s\""));
+        assert!(!analyse_line(&mut analyser, "f g() int -> x;"));
+        assert!(analyse_line(&mut analyser, "f g() int -> 2; x .g"));
+        assert_eq!(
+            vec![Type::String, Type::Integer, Type::Integer],
+            analyser.stack_types()
+        );
+        assert!(!analyse_line(&mut analyser, "+ +"));
+        assert_eq!(3, analyser.stack_types().len());
+    }
+
+    #[test]
+    fn ensure_floats_only_mix_through_conversions() {
+        assert!(
+            analyse("f avg(int x, int n) float -> x itof n itof /; f main -> 1 2 .avg drop;")
+                .is_ok()
+        );
+        assert!(analyse("f main -> 1.5 2.5 < drop;").is_ok());
+        assert!(analyse("f main -> 1 2.0 + drop;")
+            .unwrap_err()
+            .contains("provided 'int' and 'float'"));
+        assert!(analyse("f main -> 7.5 2.0 % drop;").is_err());
+        assert!(analyse("f main -> 1 ftoi drop;").is_err());
+    }
+
+    #[test]
+    fn ensure_loops_keep_the_stack_depth_of_their_entry() {
+        assert!(analyse("f main -> int i 0 = while i 5 < -> i i 1 + =;;").is_ok());
+        assert_eq!(
+            Err("[Type Error]: 'while' body must not consume values from outside of the loop at 1:27".to_string()),
+            analyse("f main -> int i 0 = 1 2 3 while i 5 < -> i i 1 + = drop; drop drop;")
+        );
+        assert_eq!(
+            Err("[Type Error]: 'break' must leave the stack as it was when the loop started at 1:44".to_string()),
+            analyse("f g() int -> 42 while true -> 7 true if -> break; drop;; f main -> .g .write;")
+        );
+        assert!(analyse("f main -> while true -> 7 true if -> drop break; drop;;").is_ok());
+        assert_eq!(
+            Err("[Type Error]: 'if' block must leave the stack as it found it at 1:34".to_string()),
+            analyse("f main -> 1 while true -> 2 true if -> drop; drop; drop;")
+        );
+    }
+
+    #[test]
+    fn ensure_if_blocks_agree_on_the_stack_they_leave() {
+        assert_eq!(
+            Err("[Type Error]: 'if' block must leave the stack as it found it at 1:18".to_string()),
+            analyse("f main -> 1 true if -> .write;;")
+        );
+        assert_eq!(
+            Err("[Type Error]: 'if' block must leave the stack as it found it at 1:25".to_string()),
+            analyse("f g(int x) int -> x 0 > if -> 1; 2; f main -> 3 .g .write;")
+        );
+        assert!(analyse(
+            "f sq(int x) int -> x x *; f main -> 5 \"s\" false if -> drop; .sq .write;"
+        )
+        .is_err());
+        assert_eq!(
+            Err("[Type Error]: 'if' and 'else' blocks must leave the same values on the stack at 1:25".to_string()),
+            analyse("f g(int x) int -> x 0 > if -> 1; else -> \"s\";; f main -> 3 .g .write;")
+        );
+        assert!(
+            analyse("f g(int x) int -> x 0 > if -> 1; else -> 2;; f main -> 3 .g .write;").is_ok()
+        );
+        assert!(
+            analyse("f g(int x) int -> x 0 > if -> 1 return; 2; f main -> 3 .g .write;").is_ok()
+        );
+    }
+
+    #[test]
+    fn ensure_loop_bodies_are_analysed_after_a_bad_condition() {
+        let input = "f main -> while -> 1 + ; ;".chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut ast = Parser::new(&mut l).parse().unwrap();
+        let errors = Analyser::make().analyse(&mut ast).unwrap_err();
+        let errors = errors.iter().map(|err| err.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            "[Argument Error]: 'while' expects its condition to leave a boolean value on top of the stack at 1:11",
+            errors[0]
+        );
+        assert_eq!(
+            "[Type Error]: Missing operands for '+' operation at 1:22",
+            errors[1]
+        );
+    }
+
+    #[test]
+    fn ensure_returned_values_match_the_return_type() {
+        assert_eq!(
+            Err("[Type Error]: 'return' expects exactly one value of type 'int' on the stack at 1:43".to_string()),
+            analyse("f g() int -> while true -> true if -> \"s\" return; ; 0; f main -> .g 1 + .write;")
+        );
+        assert_eq!(
+            Err(
+                "[Type Error]: 'return' from a void function must leave the stack empty at 1:38"
+                    .to_string()
+            ),
+            analyse("f main -> while true -> true if -> 1 return; ; ;")
+        );
+        assert_eq!(
+            Err("[Type Error]: 'return' expects exactly one value of type 'int' on the stack at 1:18".to_string()),
+            analyse("f g() int -> \"s\" return 5; f main -> .g .write;")
+        );
+        assert!(analyse("f g() int -> true if -> 1 return; 0; f main -> .g .write;").is_ok());
+    }
+
+    #[test]
+    fn ensure_loop_bodies_may_end_with_return() {
+        assert!(analyse("f g() int -> while true -> 5 return; 0; f main -> .g .write;").is_ok());
+        assert!(analyse(
+            "f g() int -> while true -> true if -> 5 return; ; 0; f main -> .g .write;"
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn ensure_missing_operands_for_stack_words_are_reported() {
+        assert!(analyse("f main -> 1 over;").is_err());
+        assert!(analyse("f main -> int x 1 dup =;").is_err());
+    }
+}
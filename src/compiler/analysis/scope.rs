@@ -2,8 +2,8 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use bug::stdlib::list_native_fns;
-use bug::{FnPrototype, Type};
+use crate::stdlib::Natives;
+use crate::{FnPrototype, Type};
 
 #[derive(Debug, Clone)]
 pub enum MetaObject {
@@ -11,12 +11,13 @@ pub enum MetaObject {
     FnPrototype(FnPrototype),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ScopeType {
     Global,
     Function,
 }
 
+#[derive(Clone)]
 pub struct Scope {
     pub type_: ScopeType,
     pub expected_type: Type,
@@ -25,10 +26,13 @@ pub struct Scope {
 }
 
 impl Scope {
-    pub fn make_global() -> Self {
+    pub fn make_global(natives: &Natives) -> Self {
         let mut store: HashMap<String, MetaObject> = HashMap::new();
-        for (name, native_fn) in list_native_fns() {
-            store.insert(name, MetaObject::FnPrototype(native_fn.prototype));
+        for native_fn in natives.iter() {
+            store.insert(
+                native_fn.name.clone(),
+                MetaObject::FnPrototype(native_fn.prototype.clone()),
+            );
         }
         Self {
             type_: ScopeType::Global,
@@ -51,22 +55,22 @@ impl Scope {
         self.store.insert(name, val);
     }
 
+    /// Whether `name` is bound from this scope, skipping the variables of the parents like `lookup`
     pub fn is_declared(&self, name: &str) -> bool {
-        if self.store.contains_key(name) {
-            return true;
-        }
-        match self.parent {
-            Some(ref parent) => parent.borrow().is_declared(name),
-            None => false,
-        }
+        self.lookup(name).is_some()
     }
 
+    /// Variables are only visible in the scope declaring them, functions can't see the variables
+    /// declared at the REPL
     pub fn lookup(&self, name: &str) -> Option<MetaObject> {
         if let Some(obj) = self.store.get(name) {
             return Some(obj.clone());
         }
         match self.parent {
-            Some(ref parent) => parent.borrow().lookup(name),
+            Some(ref parent) => match parent.borrow().lookup(name) {
+                Some(MetaObject::VarType(_)) => None,
+                obj => obj,
+            },
             None => None,
         }
     }
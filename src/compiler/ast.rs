@@ -0,0 +1,236 @@
+use core::fmt;
+
+use crate::compiler::span::Span;
+use crate::Type;
+
+#[derive(Debug, Clone)]
+pub enum Statement {
+    Assignment(Option<AssignmentTarget>, Span),
+    If(BlockStatement, Option<BlockStatement>, Span),
+    While(BlockStatement, BlockStatement, Span),
+    Break(Span),
+    Continue(Span),
+    Expression(Expression),
+    VariableDeclaration(VariableDeclaration),
+    FunctionDeclaration(FunctionDeclaration),
+}
+
+impl Statement {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Assignment(_, span) => *span,
+            Self::If(_, _, span) => *span,
+            Self::While(_, _, span) => *span,
+            Self::Break(span) => *span,
+            Self::Continue(span) => *span,
+            Self::Expression(expression) => expression.span(),
+            Self::VariableDeclaration(var_decl) => var_decl.span,
+            Self::FunctionDeclaration(fn_decl) => fn_decl.span,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AssignmentTarget {
+    pub name: String,
+    /// Whether the target's current value was loaded to the stack and must be discarded
+    pub is_loaded: bool,
+}
+
+pub type BlockStatement = Vec<Statement>;
+pub type Ast = BlockStatement;
+
+#[derive(Debug, Clone)]
+pub struct VariableDeclaration {
+    pub type_: Type,
+    pub name: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FnParam {
+    pub name: String,
+    pub type_: Type,
+    pub span: Span,
+}
+
+pub type FnParams = Vec<FnParam>;
+
+#[derive(Debug, Clone)]
+pub struct FunctionDeclaration {
+    pub name: String,
+    pub params: FnParams,
+    pub return_type: Type,
+    pub body: BlockStatement,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expression {
+    Identifier(String, Span),
+    Literal(Literal, Span),
+    FunctionCall(String, Span),
+    BinaryOp(BinaryOp, Span),
+    UnaryOp(UnaryOp, Span),
+    StackOp(StackOp, Span),
+    Return(Option<Type>, Span),
+}
+
+impl Expression {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Identifier(_, span) => *span,
+            Self::Literal(_, span) => *span,
+            Self::FunctionCall(_, span) => *span,
+            Self::BinaryOp(_, span) => *span,
+            Self::UnaryOp(_, span) => *span,
+            Self::StackOp(_, span) => *span,
+            Self::Return(_, span) => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Literal {
+    Int(i32),
+    Float(f64),
+    String(String),
+    Boolean(bool),
+}
+
+#[derive(Debug, Clone)]
+pub enum BinaryOp {
+    Plus(Option<Type>),
+    Minus(Option<Type>),
+    Multiply(Option<Type>),
+    Divide(Option<Type>),
+    Modulo(Option<Type>),
+    GratherThan(Option<Type>),
+    GratherOrEqual(Option<Type>),
+    LessThan(Option<Type>),
+    LessOrEqual(Option<Type>),
+    Equal(Option<Type>),
+    NotEqual(Option<Type>),
+    And(Option<Type>),
+    Or(Option<Type>),
+}
+
+impl BinaryOp {
+    /// The operands type resolved by the analyser
+    pub fn operands_type(&self) -> Option<Type> {
+        match self {
+            Self::Plus(type_)
+            | Self::Minus(type_)
+            | Self::Multiply(type_)
+            | Self::Divide(type_)
+            | Self::Modulo(type_)
+            | Self::GratherThan(type_)
+            | Self::GratherOrEqual(type_)
+            | Self::LessThan(type_)
+            | Self::LessOrEqual(type_)
+            | Self::Equal(type_)
+            | Self::NotEqual(type_)
+            | Self::And(type_)
+            | Self::Or(type_) => type_.clone(),
+        }
+    }
+
+    fn operands_type_mut(&mut self) -> &mut Option<Type> {
+        match self {
+            Self::Plus(type_)
+            | Self::Minus(type_)
+            | Self::Multiply(type_)
+            | Self::Divide(type_)
+            | Self::Modulo(type_)
+            | Self::GratherThan(type_)
+            | Self::GratherOrEqual(type_)
+            | Self::LessThan(type_)
+            | Self::LessOrEqual(type_)
+            | Self::Equal(type_)
+            | Self::NotEqual(type_)
+            | Self::And(type_)
+            | Self::Or(type_) => type_,
+        }
+    }
+
+    pub fn set_operands_type(&mut self, type_: Type) {
+        *self.operands_type_mut() = Some(type_);
+    }
+}
+
+impl fmt::Display for BinaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Plus(_) => write!(f, "+"),
+            Self::Minus(_) => write!(f, "-"),
+            Self::Multiply(_) => write!(f, "*"),
+            Self::Divide(_) => write!(f, "/"),
+            Self::Modulo(_) => write!(f, "%"),
+            Self::GratherThan(_) => write!(f, ">"),
+            Self::GratherOrEqual(_) => write!(f, ">="),
+            Self::LessThan(_) => write!(f, "<"),
+            Self::LessOrEqual(_) => write!(f, "<="),
+            Self::Equal(_) => write!(f, "=="),
+            Self::NotEqual(_) => write!(f, "!="),
+            Self::And(_) => write!(f, "and"),
+            Self::Or(_) => write!(f, "or"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum UnaryOp {
+    Not,
+    /// Converts an int to the float of same value
+    IntToFloat,
+    /// Converts a float to an int, truncating toward zero
+    FloatToInt,
+}
+
+impl fmt::Display for UnaryOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Not => write!(f, "not"),
+            Self::IntToFloat => write!(f, "itof"),
+            Self::FloatToInt => write!(f, "ftoi"),
+        }
+    }
+}
+
+/// Built-in words shuffling the values on top of the stack
+#[derive(Debug, Clone)]
+pub enum StackOp {
+    /// ( a -- a a )
+    Dup,
+    /// ( a -- )
+    Drop,
+    /// ( a b -- b a )
+    Swap,
+    /// ( a b -- a b a )
+    Over,
+    /// ( a b c -- b c a )
+    Rot,
+}
+
+impl StackOp {
+    /// The amount of values the word expects on top of the stack
+    pub fn depth(&self) -> usize {
+        match self {
+            Self::Dup | Self::Drop => 1,
+            Self::Swap | Self::Over => 2,
+            Self::Rot => 3,
+        }
+    }
+}
+
+impl fmt::Display for StackOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dup => write!(f, "dup"),
+            Self::Drop => write!(f, "drop"),
+            Self::Swap => write!(f, "swap"),
+            Self::Over => write!(f, "over"),
+            Self::Rot => write!(f, "rot"),
+        }
+    }
+}
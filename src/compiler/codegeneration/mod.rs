@@ -0,0 +1,608 @@
+use std::collections::HashMap;
+
+use crate::bytecode::{ByteCodeStream, Opcode, PushOperand};
+use crate::compiler::ast::*;
+use crate::compiler::span::Span;
+use crate::{DefinedFn, LineTable, Object, Pool, PoolEntry, Program, Type};
+
+#[derive(Clone)]
+struct Context {
+    bytecode: ByteCodeStream,
+    locals: HashMap<String, Local>,
+    loops: Vec<Loop>,
+    lines: LineTable,
+    /// Return type of the function being generated
+    return_type: Type,
+}
+
+impl Context {
+    pub fn make() -> Self {
+        Self {
+            bytecode: ByteCodeStream::empty(),
+            locals: HashMap::new(),
+            loops: vec![],
+            lines: LineTable::make(),
+            return_type: Type::Void,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.bytecode.clear();
+        self.locals.clear();
+        self.loops.clear();
+        self.lines = LineTable::make();
+        self.return_type = Type::Void;
+    }
+
+    /// Records that the next generated instructions come from the line of `span`
+    pub fn mark_line(&mut self, span: Span) {
+        self.lines.mark(self.bytecode.get_pos(), span.line);
+    }
+}
+
+/// Keeps track of the enclosing loop, `breaks` holds the offsets of the jumps to be patched with
+/// the loop's exit offset
+#[derive(Clone)]
+struct Loop {
+    start: usize,
+    breaks: Vec<usize>,
+}
+
+#[derive(Clone)]
+struct Local {
+    index: usize,
+    type_: Type,
+}
+
+impl Local {
+    fn make(index: usize, type_: Type) -> Self {
+        Self { index, type_ }
+    }
+}
+
+#[derive(Clone)]
+pub struct CodeGenerator {
+    pool: Pool,
+    fns: HashMap<String, DefinedFn>,
+    /// Index of every defined function in the generated program, known before any call is
+    /// generated so functions can be called before their declaration
+    fn_indices: HashMap<String, usize>,
+    /// Return type of every defined function, known along with its index
+    return_types: HashMap<String, Type>,
+    natives: Vec<String>,
+    context: Context,
+    /// Variables declared by the lines generated with `gen_line`
+    line_locals: HashMap<String, Local>,
+}
+
+impl CodeGenerator {
+    pub fn make() -> Self {
+        Self {
+            pool: Pool::make(),
+            fns: HashMap::new(),
+            fn_indices: HashMap::new(),
+            return_types: HashMap::new(),
+            natives: vec![],
+            context: Context::make(),
+            line_locals: HashMap::new(),
+        }
+    }
+
+    pub fn gen(&mut self, ast: Ast) -> Program {
+        self.declare_fns(&ast);
+        for stmt in ast {
+            self.generate_statement(stmt);
+        }
+        self.program()
+    }
+
+    /// Generates a line typed at the REPL: its functions join the ones generated before and the
+    /// rest of its statements become the body of a function called `entry`, whose locals are
+    /// shared by every line. The program holds all the functions generated so far, `entry` last
+    pub fn gen_line(&mut self, ast: Ast, entry: &str) -> Program {
+        self.declare_fns(&ast);
+        let (fn_decls, stmts): (Ast, Ast) = ast
+            .into_iter()
+            .partition(|stmt| matches!(stmt, Statement::FunctionDeclaration(_)));
+        for stmt in fn_decls {
+            self.generate_statement(stmt);
+        }
+
+        self.context.reset();
+        self.context.locals = std::mem::take(&mut self.line_locals);
+        for stmt in stmts {
+            self.generate_statement(stmt);
+        }
+        self.context.bytecode.push(Opcode::Return);
+        let entry_fn = DefinedFn {
+            name: entry.to_string(),
+            arity: 0,
+            code: self.context.bytecode.clone(),
+            max_locals: self.context.locals.len(),
+            lines: self.context.lines.clone(),
+        };
+        self.line_locals = std::mem::take(&mut self.context.locals);
+
+        let mut program = self.program();
+        program.fns.push(entry_fn);
+        program
+    }
+
+    fn declare_fns(&mut self, ast: &Ast) {
+        for stmt in ast {
+            if let Statement::FunctionDeclaration(fn_decl) = stmt {
+                let index = self.fn_indices.len();
+                self.fn_indices.entry(fn_decl.name.clone()).or_insert(index);
+                self.return_types
+                    .insert(fn_decl.name.clone(), fn_decl.return_type.clone());
+            }
+        }
+    }
+
+    fn program(&self) -> Program {
+        let mut fns = self.fns.values().cloned().collect::<Vec<DefinedFn>>();
+        fns.sort_by_key(|defined_fn| self.fn_indices[&defined_fn.name]);
+        Program {
+            pool: self.pool.to_owned(),
+            fns,
+            natives: self.natives.to_owned(),
+        }
+    }
+
+    fn generate_statement(&mut self, stmt: Statement) {
+        self.context.mark_line(stmt.span());
+        match stmt {
+            Statement::If(consequence, alternative, _) => {
+                self.generate_if_statement(consequence, alternative, false)
+            }
+            Statement::While(condition, body, _) => self.generate_while_statement(condition, body),
+            Statement::Break(_) => self.generate_break(),
+            Statement::Continue(_) => self.generate_continue(),
+            Statement::Expression(expr) => self.generate_expression(expr),
+            Statement::FunctionDeclaration(fn_decl) => self.generate_function_declaration(fn_decl),
+            Statement::VariableDeclaration(var_decl) => self.generate_variable_decl(var_decl),
+            Statement::Assignment(target, _) => self.generate_assignment(target),
+        }
+    }
+
+    fn generate_assignment(&mut self, target: Option<AssignmentTarget>) {
+        let target = target.unwrap();
+        self.context.bytecode.push(Opcode::LStore(
+            self.context.locals.get(&target.name).unwrap().index,
+        ));
+        if target.is_loaded {
+            self.context.bytecode.push(Opcode::Drop);
+        }
+    }
+
+    fn generate_variable_decl(&mut self, var_decl: VariableDeclaration) {
+        self.context.locals.insert(
+            var_decl.name,
+            Local::make(self.context.locals.len(), var_decl.type_),
+        );
+    }
+
+    /// Generates a statement which is the last thing executed before its function returns, calls
+    /// to defined functions at this position reuse the caller's frame when the callee returns the
+    /// same as the caller, its return then being the caller's
+    fn generate_tail_statement(&mut self, stmt: Statement) {
+        self.context.mark_line(stmt.span());
+        match stmt {
+            Statement::Expression(Expression::FunctionCall(fn_name, _))
+                if self.return_types.get(&fn_name) == Some(&self.context.return_type) =>
+            {
+                let index = self.fn_indices[&fn_name];
+                self.context.bytecode.push(Opcode::TailInvoke(index))
+            }
+            Statement::If(consequence, alternative, _) => {
+                self.generate_if_statement(consequence, alternative, true)
+            }
+            stmt => self.generate_statement(stmt),
+        }
+    }
+
+    fn generate_block(&mut self, block: BlockStatement, is_tail: bool) {
+        let last_index = block.len().saturating_sub(1);
+        for (index, stmt) in block.into_iter().enumerate() {
+            if is_tail && index == last_index {
+                self.generate_tail_statement(stmt);
+            } else {
+                self.generate_statement(stmt);
+            }
+        }
+    }
+
+    fn generate_if_statement(
+        &mut self,
+        consequence: BlockStatement,
+        alternative: Option<BlockStatement>,
+        is_tail: bool,
+    ) {
+        if let Some(alternative) = alternative {
+            let before_if_offset = self.context.bytecode.get_pos();
+            self.context.bytecode.push(Opcode::Nop);
+            self.generate_block(consequence, is_tail);
+            let after_if_offset = self.context.bytecode.get_pos();
+            self.context.bytecode.push(Opcode::Nop);
+            self.generate_block(alternative, is_tail);
+            let after_else_offset = self.context.bytecode.get_pos();
+            self.context
+                .bytecode
+                .push_at(Opcode::JumpIfFalse(after_if_offset + 1), before_if_offset);
+            self.context
+                .bytecode
+                .push_at(Opcode::Jump(after_else_offset), after_if_offset);
+        } else {
+            let before_if_offset = self.context.bytecode.get_pos();
+            self.context.bytecode.push(Opcode::Nop);
+            self.generate_block(consequence, is_tail);
+            let after_if_offset = self.context.bytecode.get_pos();
+            self.context
+                .bytecode
+                .push_at(Opcode::JumpIfFalse(after_if_offset), before_if_offset);
+        }
+    }
+
+    fn generate_while_statement(&mut self, condition: BlockStatement, body: BlockStatement) {
+        let start_offset = self.context.bytecode.get_pos();
+        for stmt in condition {
+            self.generate_statement(stmt);
+        }
+        let exit_jump_offset = self.context.bytecode.get_pos();
+        self.context.bytecode.push(Opcode::Nop);
+
+        self.context.loops.push(Loop {
+            start: start_offset,
+            breaks: vec![],
+        });
+        for stmt in body {
+            self.generate_statement(stmt);
+        }
+        self.context.bytecode.push(Opcode::Jump(start_offset));
+        let loop_ = self.context.loops.pop().unwrap();
+
+        let after_loop_offset = self.context.bytecode.get_pos();
+        self.context
+            .bytecode
+            .push_at(Opcode::JumpIfFalse(after_loop_offset), exit_jump_offset);
+        for offset in loop_.breaks {
+            self.context
+                .bytecode
+                .push_at(Opcode::Jump(after_loop_offset), offset);
+        }
+    }
+
+    fn generate_break(&mut self) {
+        let offset = self.context.bytecode.get_pos();
+        self.context.bytecode.push(Opcode::Nop);
+        self.context.loops.last_mut().unwrap().breaks.push(offset);
+    }
+
+    fn generate_continue(&mut self) {
+        let start_offset = self.context.loops.last().unwrap().start;
+        self.context.bytecode.push(Opcode::Jump(start_offset));
+    }
+
+    fn generate_function_declaration(&mut self, fn_decl: FunctionDeclaration) {
+        self.context.reset();
+
+        self.context.return_type = fn_decl.return_type.clone();
+        let arity = fn_decl.params.len();
+        for (i, p) in fn_decl.params.into_iter().enumerate() {
+            self.context.locals.insert(p.name, Local::make(i, p.type_));
+        }
+        self.generate_block(fn_decl.body, true);
+
+        match fn_decl.return_type {
+            Type::Void => self.context.bytecode.push(Opcode::Return),
+            _ => self.context.bytecode.push(Opcode::ReturnTop),
+        }
+
+        self.fns.insert(
+            fn_decl.name.clone(),
+            DefinedFn {
+                name: fn_decl.name,
+                arity,
+                code: self.context.bytecode.clone(),
+                max_locals: self.context.locals.len(),
+                lines: self.context.lines.clone(),
+            },
+        );
+    }
+
+    fn generate_expression(&mut self, expression: Expression) {
+        match expression {
+            Expression::Literal(literal, _) => self.generate_literal(literal),
+            Expression::FunctionCall(fn_name, _) => self.generate_function_call(fn_name),
+            Expression::BinaryOp(binop, _) => self.generate_binop(binop),
+            Expression::UnaryOp(unop, _) => self.generate_unop(unop),
+            Expression::StackOp(stackop, _) => self.generate_stackop(stackop),
+            Expression::Identifier(ident, _) => self.generate_identifier(ident),
+            Expression::Return(type_, _) => self.generate_return_expression(type_.unwrap()),
+        }
+    }
+
+    fn generate_identifier(&mut self, ident: String) {
+        let local = self
+            .context
+            .locals
+            .get(&ident)
+            .unwrap_or_else(|| panic!("Expected '{}' to a local", &ident));
+        match local.type_ {
+            Type::Integer | Type::Boolean | Type::String | Type::Float => {
+                self.context.bytecode.push(Opcode::LLoad(local.index))
+            }
+            _ => unreachable!(),
+        };
+    }
+
+    fn generate_binop(&mut self, binop: BinaryOp) {
+        let opcode = match (&binop, binop.operands_type().unwrap()) {
+            (BinaryOp::Plus(_), Type::Integer) => Opcode::IAdd,
+            (BinaryOp::Minus(_), Type::Integer) => Opcode::ISub,
+            (BinaryOp::Multiply(_), Type::Integer) => Opcode::IMul,
+            (BinaryOp::Divide(_), Type::Integer) => Opcode::IDiv,
+            (BinaryOp::Modulo(_), Type::Integer) => Opcode::IRem,
+            (BinaryOp::GratherThan(_), Type::Integer) => Opcode::ICmpGT,
+            (BinaryOp::GratherOrEqual(_), Type::Integer) => Opcode::ICmpGE,
+            (BinaryOp::LessThan(_), Type::Integer) => Opcode::ICmpLT,
+            (BinaryOp::LessOrEqual(_), Type::Integer) => Opcode::ICmpLE,
+            (BinaryOp::Equal(_), Type::Integer) => Opcode::ICmpEQ,
+            (BinaryOp::NotEqual(_), Type::Integer) => Opcode::ICmpNE,
+            (BinaryOp::Plus(_), Type::Float) => Opcode::FAdd,
+            (BinaryOp::Minus(_), Type::Float) => Opcode::FSub,
+            (BinaryOp::Multiply(_), Type::Float) => Opcode::FMul,
+            (BinaryOp::Divide(_), Type::Float) => Opcode::FDiv,
+            (BinaryOp::GratherThan(_), Type::Float) => Opcode::FCmpGT,
+            (BinaryOp::GratherOrEqual(_), Type::Float) => Opcode::FCmpGE,
+            (BinaryOp::LessThan(_), Type::Float) => Opcode::FCmpLT,
+            (BinaryOp::LessOrEqual(_), Type::Float) => Opcode::FCmpLE,
+            (BinaryOp::Equal(_), Type::Float) => Opcode::FCmpEQ,
+            (BinaryOp::NotEqual(_), Type::Float) => Opcode::FCmpNE,
+            (BinaryOp::Equal(_), Type::Boolean | Type::String) => Opcode::CmpEQ,
+            (BinaryOp::NotEqual(_), Type::Boolean | Type::String) => Opcode::CmpNE,
+            (BinaryOp::And(_), Type::Boolean) => Opcode::And,
+            (BinaryOp::Or(_), Type::Boolean) => Opcode::Or,
+            _ => unreachable!(),
+        };
+        self.context.bytecode.push(opcode);
+    }
+
+    fn generate_unop(&mut self, unop: UnaryOp) {
+        let opcode = match unop {
+            UnaryOp::Not => Opcode::Not,
+            UnaryOp::IntToFloat => Opcode::I2F,
+            UnaryOp::FloatToInt => Opcode::F2I,
+        };
+        self.context.bytecode.push(opcode);
+    }
+
+    fn generate_stackop(&mut self, stackop: StackOp) {
+        let opcode = match stackop {
+            StackOp::Dup => Opcode::Dup,
+            StackOp::Drop => Opcode::Drop,
+            StackOp::Swap => Opcode::Swap,
+            StackOp::Over => Opcode::Over,
+            StackOp::Rot => Opcode::Rot,
+        };
+        self.context.bytecode.push(opcode);
+    }
+
+    fn generate_function_call(&mut self, fn_name: String) {
+        if let Some(index) = self.fn_indices.get(&fn_name) {
+            self.context.bytecode.push(Opcode::Invoke(*index));
+            return;
+        }
+        let index = match self.natives.iter().position(|name| *name == fn_name) {
+            Some(index) => index,
+            None => {
+                self.natives.push(fn_name);
+                self.natives.len() - 1
+            }
+        };
+        self.context.bytecode.push(Opcode::InvokeNative(index));
+    }
+
+    fn generate_literal(&mut self, literal: Literal) {
+        match literal {
+            Literal::Int(x) => self
+                .context
+                .bytecode
+                .push(Opcode::Push(PushOperand::Integer(x))),
+            Literal::Boolean(x) => self
+                .context
+                .bytecode
+                .push(Opcode::Push(PushOperand::Boolean(x))),
+            Literal::String(x) => self.context.bytecode.push(Opcode::Ldc(
+                self.pool.append(PoolEntry::Object(Object::String(x))),
+            )),
+            Literal::Float(x) => self.context.bytecode.push(Opcode::Ldc(
+                self.pool.append(PoolEntry::Object(Object::Float(x))),
+            )),
+        };
+    }
+
+    fn generate_return_expression(&mut self, type_: Type) {
+        match type_ {
+            Type::Void => self.context.bytecode.push(Opcode::Return),
+            _ => self.context.bytecode.push(Opcode::ReturnTop),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compiler::span::Span;
+    use crate::Type;
+
+    #[test]
+    fn enaure_hello_world_is_well_generated() {
+        let ast = vec![Statement::FunctionDeclaration(FunctionDeclaration {
+            name: "main".to_string(),
+            params: vec![],
+            return_type: Type::Void,
+            body: vec![
+                Statement::Expression(Expression::Literal(
+                    Literal::String("Hello, world!".to_string()),
+                    Span::default(),
+                )),
+                Statement::Expression(Expression::FunctionCall(
+                    "write".to_string(),
+                    Span::default(),
+                )),
+            ],
+            span: Span::default(),
+        })];
+
+        let mut generator = CodeGenerator::make();
+        let program = generator.gen(ast);
+
+        assert!(program.fn_index("main").is_some());
+        assert!(program
+            .pool
+            .entries
+            .contains(&PoolEntry::Object(Object::String(
+                "Hello, world!".to_string()
+            ))));
+
+        let main_code = program.fns[program.fn_index("main").unwrap()].code.clone();
+
+        match main_code.get_at(0).unwrap() {
+            Opcode::Ldc(_) => {}
+            x => panic!("Unexpected instruction {}", x),
+        }
+
+        match main_code.get_at(1).unwrap() {
+            Opcode::InvokeNative(_) => {}
+            x => panic!("Unexpected instruction {}", x),
+        }
+
+        match main_code.get_at(2).unwrap() {
+            Opcode::Return => {}
+            x => panic!("Unexpected instruction {}", x),
+        }
+    }
+
+    #[test]
+    fn ensure_while_jumps_back_to_its_condition() {
+        let ast = vec![Statement::FunctionDeclaration(FunctionDeclaration {
+            name: "main".to_string(),
+            params: vec![],
+            return_type: Type::Void,
+            body: vec![Statement::While(
+                vec![Statement::Expression(Expression::Literal(
+                    Literal::Boolean(true),
+                    Span::default(),
+                ))],
+                vec![Statement::Break(Span::default())],
+                Span::default(),
+            )],
+            span: Span::default(),
+        })];
+
+        let mut generator = CodeGenerator::make();
+        let program = generator.gen(ast);
+        let main_code = program.fns[program.fn_index("main").unwrap()].code.clone();
+
+        match main_code.get_at(1).unwrap() {
+            Opcode::JumpIfFalse(4) => {}
+            x => panic!("Unexpected instruction {}", x),
+        }
+
+        match main_code.get_at(2).unwrap() {
+            Opcode::Jump(4) => {}
+            x => panic!("Unexpected instruction {}", x),
+        }
+
+        match main_code.get_at(3).unwrap() {
+            Opcode::Jump(0) => {}
+            x => panic!("Unexpected instruction {}", x),
+        }
+    }
+
+    #[test]
+    fn ensure_call_in_tail_position_reuses_frame() {
+        let ast = vec![Statement::FunctionDeclaration(FunctionDeclaration {
+            name: "loop".to_string(),
+            params: vec![],
+            return_type: Type::Void,
+            body: vec![
+                Statement::Expression(Expression::Literal(
+                    Literal::String("looping".to_string()),
+                    Span::default(),
+                )),
+                Statement::Expression(Expression::FunctionCall(
+                    "write".to_string(),
+                    Span::default(),
+                )),
+                Statement::Expression(Expression::FunctionCall(
+                    "loop".to_string(),
+                    Span::default(),
+                )),
+            ],
+            span: Span::default(),
+        })];
+
+        let mut generator = CodeGenerator::make();
+        let program = generator.gen(ast);
+        let loop_code = program.fns[program.fn_index("loop").unwrap()].code.clone();
+
+        match loop_code.get_at(1).unwrap() {
+            Opcode::InvokeNative(index) => assert_eq!("write", program.natives[*index]),
+            x => panic!("Unexpected instruction {}", x),
+        }
+
+        match loop_code.get_at(2).unwrap() {
+            Opcode::TailInvoke(index) => assert_eq!(program.fn_index("loop"), Some(*index)),
+            x => panic!("Unexpected instruction {}", x),
+        }
+    }
+
+    #[test]
+    fn ensure_tail_call_returning_something_else_is_a_plain_call() {
+        let source = "f h() -> \"in h\" .write; f g() int -> 5 .h; f main -> .g .write;";
+        let program = crate::compiler::compile(source).unwrap();
+        let g_code = &program.fns[program.fn_index("g").unwrap()].code;
+
+        match g_code.get_at(1).unwrap() {
+            Opcode::Invoke(index) => assert_eq!(program.fn_index("h"), Some(*index)),
+            x => panic!("Unexpected instruction {}", x),
+        }
+        assert!(crate::vm::verifier::verify(&program, &crate::stdlib::list_native_fns()).is_ok());
+    }
+
+    #[test]
+    fn ensure_line_table_maps_instructions_to_source_lines() {
+        let ast = vec![Statement::FunctionDeclaration(FunctionDeclaration {
+            name: "main".to_string(),
+            params: vec![],
+            return_type: Type::Void,
+            body: vec![
+                Statement::Expression(Expression::Literal(
+                    Literal::String("first".to_string()),
+                    Span::make(2, 3, 7),
+                )),
+                Statement::Expression(Expression::FunctionCall(
+                    "write".to_string(),
+                    Span::make(2, 11, 6),
+                )),
+                Statement::Expression(Expression::Literal(Literal::Int(1), Span::make(4, 3, 1))),
+                Statement::Expression(Expression::FunctionCall(
+                    "write".to_string(),
+                    Span::make(4, 5, 6),
+                )),
+            ],
+            span: Span::make(1, 1, 1),
+        })];
+
+        let mut generator = CodeGenerator::make();
+        let program = generator.gen(ast);
+        let lines = &program.fns[program.fn_index("main").unwrap()].lines;
+
+        assert_eq!(Some(2), lines.line_at(0));
+        assert_eq!(Some(2), lines.line_at(1));
+        assert_eq!(Some(4), lines.line_at(2));
+        assert_eq!(Some(4), lines.line_at(4));
+    }
+}
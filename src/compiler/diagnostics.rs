@@ -0,0 +1,128 @@
+use core::fmt;
+
+use crate::compiler::span::Span;
+
+/// A compiler error ready to be reported to the user, rendered in a rustc-like style with the
+/// offending source line and a caret underline
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub title: String,
+    pub msg: String,
+    pub span: Option<Span>,
+    /// File the source was read from, `None` until the caller names it with `in_file`
+    pub path: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn make(title: String, msg: String, span: Option<Span>) -> Self {
+        Self {
+            title,
+            msg,
+            span,
+            path: None,
+        }
+    }
+
+    /// Attaches the file the diagnosed source was read from
+    pub fn in_file(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+
+    /// Renders the diagnostic against `source`, the text of the file it was reported in
+    pub fn render(&self, source: &str) -> String {
+        let mut out = format!("error{}: {}\n", self.title, self.msg);
+        let path = self.path.as_deref().unwrap_or("<input>");
+        let span = match self.span {
+            Some(span) => span,
+            None => {
+                out.push_str(&format!(" --> {}\n", path));
+                return out;
+            }
+        };
+
+        let line = source
+            .lines()
+            .nth(span.line.saturating_sub(1))
+            .unwrap_or("");
+        let gutter = " ".repeat(span.line.to_string().len());
+        let line_len = line.chars().count();
+        let colm = span.colm.max(1);
+        let width = if colm > line_len {
+            1
+        } else {
+            span.len.clamp(1, line_len - colm + 1)
+        };
+
+        out.push_str(&format!("{}--> {}:{}\n", gutter, path, span));
+        out.push_str(&format!("{} |\n", gutter));
+        out.push_str(&format!("{} | {}\n", span.line, line));
+        out.push_str(&format!(
+            "{} | {}{}\n",
+            gutter,
+            " ".repeat(colm - 1),
+            "^".repeat(width)
+        ));
+        out
+    }
+}
+
+/// Renders every diagnostic against `source` separated by a blank line, the way all the tools
+/// report compiler errors
+pub fn render_all(diagnostics: &[Diagnostic], source: &str) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.render(source))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.title, self.msg)?;
+        match (&self.path, self.span) {
+            (Some(path), Some(span)) => write!(f, " at {}:{}", path, span),
+            (Some(path), None) => write!(f, " in {}", path),
+            (None, Some(span)) => write!(f, " at {}", span),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_all, Diagnostic};
+    use crate::compiler::span::Span;
+
+    #[test]
+    fn ensure_caret_points_to_offending_token() {
+        let source = "f main ->\n  1 + .write;";
+        let diagnostic = Diagnostic::make(
+            "[Type Error]".to_string(),
+            "Missing operands for '+' operation".to_string(),
+            Some(Span::make(2, 5, 1)),
+        )
+        .in_file("main.bug");
+        let expected = "error[Type Error]: Missing operands for '+' operation\n \
+                        --> main.bug:2:5\n  \
+                        |\n\
+                        2 |   1 + .write;\n  \
+                        |     ^\n";
+        assert_eq!(expected, diagnostic.render(source));
+        assert_eq!(
+            "[Type Error]: Missing operands for '+' operation at main.bug:2:5",
+            diagnostic.to_string()
+        );
+    }
+
+    #[test]
+    fn ensure_diagnostics_are_separated_by_one_blank_line() {
+        let syntax = Diagnostic::make("[Syntax Error]".to_string(), "a".to_string(), None);
+        let analysis = Diagnostic::make("[Type Error]".to_string(), "b".to_string(), None);
+        let rendered = render_all(&[syntax.in_file("x.bug"), analysis.in_file("x.bug")], "");
+        assert_eq!(
+            "error[Syntax Error]: a\n --> x.bug\n\nerror[Type Error]: b\n --> x.bug\n",
+            rendered
+        );
+    }
+}
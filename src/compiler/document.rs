@@ -0,0 +1,300 @@
+use std::collections::HashMap;
+
+use crate::compiler::analysis::Analyser;
+use crate::compiler::ast::*;
+use crate::compiler::diagnostics::Diagnostic;
+use crate::compiler::frontend::{lexer::Lexer, parser::Parser};
+use crate::compiler::span::Span;
+use crate::stdlib::Natives;
+use crate::{FnPrototype, Type};
+
+/// A function callable from the document, as completion and hover show it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub name: String,
+    /// Declaration like `f sum(int lhs, int rhs) int`
+    pub label: String,
+    /// Span of the function's name in its declaration, `None` for natives
+    pub span: Option<Span>,
+}
+
+enum Target {
+    Fn(String),
+    Var(Span),
+}
+
+/// A word naming a function or a variable declared elsewhere
+struct Reference {
+    span: Span,
+    target: Target,
+}
+
+/// What the editor support knows about a source file: its errors, the functions it can call, the
+/// words referring to declarations and the types on the stack after each word
+pub struct Document {
+    pub diagnostics: Vec<Diagnostic>,
+    signatures: Vec<Signature>,
+    references: Vec<Reference>,
+    stacks: Vec<(Span, Vec<Type>)>,
+}
+
+impl Document {
+    pub fn make(source: &str, natives: &Natives) -> Self {
+        let mut signatures = natives
+            .iter()
+            .map(|native_fn| Signature {
+                name: native_fn.name.clone(),
+                label: native_label(&native_fn.name, &native_fn.prototype),
+                span: None,
+            })
+            .collect::<Vec<Signature>>();
+
+        let input = source.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut ast = match Parser::new(&mut l).parse() {
+            Ok(ast) => ast,
+            Err(err) => {
+                return Self {
+                    diagnostics: vec![err.into()],
+                    signatures,
+                    references: vec![],
+                    stacks: vec![],
+                }
+            }
+        };
+
+        for stmt in &ast {
+            if let Statement::FunctionDeclaration(fn_decl) = stmt {
+                signatures.retain(|signature| signature.name != fn_decl.name);
+                signatures.push(Signature {
+                    name: fn_decl.name.clone(),
+                    label: fn_label(fn_decl),
+                    span: Some(fn_decl.span),
+                });
+            }
+        }
+        let mut references = vec![];
+        collect_references(&ast, &mut HashMap::new(), &signatures, &mut references);
+
+        let mut analyser = Analyser::make_with(natives);
+        analyser.record_stacks();
+        let diagnostics = match analyser.analyse(&mut ast) {
+            Ok(()) => vec![],
+            Err(errors) => errors.into_iter().map(Diagnostic::from).collect(),
+        };
+
+        Self {
+            diagnostics,
+            signatures,
+            references,
+            stacks: analyser.recorded_stacks().to_vec(),
+        }
+    }
+
+    /// Text to show for the word at `line` and `colm`: the signature of the function it calls or
+    /// declares and the types it leaves on the stack, top last
+    pub fn hover(&self, line: usize, colm: usize) -> Option<String> {
+        let mut sections = vec![];
+        let signature = match self.reference_at(line, colm) {
+            Some(Reference {
+                target: Target::Fn(name),
+                ..
+            }) => self.signature(name),
+            _ => self
+                .signatures
+                .iter()
+                .find(|signature| signature.span.is_some_and(|x| contains(x, line, colm))),
+        };
+        if let Some(signature) = signature {
+            sections.push(format!("```bug\n{}\n```", signature.label));
+        }
+        if let Some((_, types)) = self.stacks.iter().find(|(x, _)| contains(*x, line, colm)) {
+            let types = types.iter().map(Type::to_string).collect::<Vec<String>>();
+            sections.push(format!("stack: [{}]", types.join(", ")));
+        }
+        match sections.is_empty() {
+            true => None,
+            false => Some(sections.join("\n\n")),
+        }
+    }
+
+    /// Where the function or variable named by the word at `line` and `colm` is declared
+    pub fn definition(&self, line: usize, colm: usize) -> Option<Span> {
+        match &self.reference_at(line, colm)?.target {
+            Target::Fn(name) => self.signature(name)?.span,
+            Target::Var(span) => Some(*span),
+        }
+    }
+
+    /// The natives followed by the functions declared in the document
+    pub fn completions(&self) -> &[Signature] {
+        &self.signatures
+    }
+
+    fn reference_at(&self, line: usize, colm: usize) -> Option<&Reference> {
+        self.references
+            .iter()
+            .find(|reference| contains(reference.span, line, colm))
+    }
+
+    fn signature(&self, name: &str) -> Option<&Signature> {
+        self.signatures
+            .iter()
+            .find(|signature| signature.name == name)
+    }
+}
+
+/// Whether the cursor at `line` and `colm` is on the span or right after it
+fn contains(span: Span, line: usize, colm: usize) -> bool {
+    span.line == line && colm >= span.colm && colm <= span.colm + span.len
+}
+
+fn fn_label(fn_decl: &FunctionDeclaration) -> String {
+    let params = fn_decl
+        .params
+        .iter()
+        .map(|param| format!("{} {}", param.type_, param.name))
+        .collect();
+    label(&fn_decl.name, params, &fn_decl.return_type)
+}
+
+/// Natives declaring fewer types than their arity accept a value of any type for the rest
+fn native_label(name: &str, prototype: &FnPrototype) -> String {
+    let params = (0..prototype.arity as usize)
+        .map(|index| match prototype.argtypes.get(index) {
+            Some(type_) => type_.to_string(),
+            None => "any".to_string(),
+        })
+        .collect();
+    label(name, params, &prototype.return_type)
+}
+
+fn label(name: &str, params: Vec<String>, return_type: &Type) -> String {
+    let mut label = format!("f {}", name);
+    if !params.is_empty() {
+        label.push_str(&format!("({})", params.join(", ")));
+    }
+    if *return_type != Type::Void {
+        label.push_str(&format!(" {}", return_type));
+    }
+    label
+}
+
+fn collect_references(
+    block: &BlockStatement,
+    vars: &mut HashMap<String, Span>,
+    signatures: &[Signature],
+    references: &mut Vec<Reference>,
+) {
+    for stmt in block {
+        match stmt {
+            Statement::FunctionDeclaration(fn_decl) => {
+                let mut vars = fn_decl
+                    .params
+                    .iter()
+                    .map(|param| (param.name.clone(), param.span))
+                    .collect();
+                collect_references(&fn_decl.body, &mut vars, signatures, references);
+            }
+            Statement::VariableDeclaration(var_decl) => {
+                vars.insert(var_decl.name.clone(), var_decl.span);
+            }
+            Statement::If(consequence, alternative, _) => {
+                collect_references(consequence, vars, signatures, references);
+                if let Some(alternative) = alternative {
+                    collect_references(alternative, vars, signatures, references);
+                }
+            }
+            Statement::While(condition, body, _) => {
+                collect_references(condition, vars, signatures, references);
+                collect_references(body, vars, signatures, references);
+            }
+            Statement::Expression(Expression::FunctionCall(name, span))
+                if signatures.iter().any(|signature| signature.name == *name) =>
+            {
+                references.push(Reference {
+                    span: *span,
+                    target: Target::Fn(name.clone()),
+                });
+            }
+            Statement::Expression(Expression::Identifier(name, span)) => {
+                if let Some(declaration) = vars.get(name) {
+                    references.push(Reference {
+                        span: *span,
+                        target: Target::Var(*declaration),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::list_native_fns;
+
+    const SOURCE: &str = "\
+f sum(int lhs, int rhs) int ->
+  lhs rhs +;
+
+f main ->
+  34 35 .sum .write;";
+
+    #[test]
+    fn ensure_hover_shows_signatures_and_stack_types() {
+        let document = Document::make(SOURCE, &list_native_fns());
+        assert!(document.diagnostics.is_empty());
+        assert_eq!(
+            Some("```bug\nf sum(int lhs, int rhs) int\n```\n\nstack: [int]".to_string()),
+            document.hover(5, 10)
+        );
+        assert_eq!(
+            Some("```bug\nf write(any)\n```\n\nstack: []".to_string()),
+            document.hover(5, 15)
+        );
+        assert_eq!(Some("stack: [int, int]".to_string()), document.hover(2, 7));
+    }
+
+    #[test]
+    fn ensure_definitions_are_found() {
+        let document = Document::make(SOURCE, &list_native_fns());
+        assert_eq!(Some(Span::make(1, 3, 3)), document.definition(5, 11));
+        assert_eq!(Some(Span::make(1, 7, 7)), document.definition(2, 3));
+        assert_eq!(None, document.definition(5, 15));
+        let names = document
+            .completions()
+            .iter()
+            .map(|signature| signature.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(vec!["write", "sum", "main"], names);
+    }
+
+    #[test]
+    fn ensure_errors_are_reported() {
+        let document = Document::make("f main -> 1 +;", &list_native_fns());
+        assert_eq!(
+            "Missing operands for '+' operation",
+            document.diagnostics[0].msg
+        );
+        let document = Document::make("f main -> (", &list_native_fns());
+        assert_eq!(1, document.diagnostics.len());
+    }
+
+    #[test]
+    fn ensure_misused_words_are_diagnosed_instead_of_crashing() {
+        let document = Document::make("f main -> main;", &list_native_fns());
+        assert_eq!(
+            "'main' is a function, call it with '.main'",
+            document.diagnostics[0].msg
+        );
+        assert_eq!(Some(Span::make(1, 11, 4)), document.diagnostics[0].span);
+
+        let document = Document::make("f main -> int x return;", &list_native_fns());
+        assert_eq!(
+            "Cannot return the declaration of variable 'x'",
+            document.diagnostics[0].msg
+        );
+    }
+}
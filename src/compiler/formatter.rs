@@ -0,0 +1,235 @@
+use crate::compiler::diagnostics::Diagnostic;
+use crate::compiler::frontend::{lexer::Lexer, parser::Parser, Comment, Token};
+use crate::compiler::span::Span;
+
+/// Rewrites `source` in the canonical style: every `->` opens a block indented by two more spaces
+/// until its `;`, tokens are separated by a single space except around `.`, `(`, `)`, `,` and
+/// `;`, and at most one blank line is kept between lines. Line breaks and comments are kept where
+/// they are, so the result only differs from `source` in its whitespace
+pub fn format(source: &str) -> Result<String, Diagnostic> {
+    let input = source.chars().collect::<Vec<char>>();
+
+    // only well formed programs are formatted, the parser reports the errors
+    let mut l = Lexer::new(&input);
+    Parser::new(&mut l).parse().map_err(Diagnostic::from)?;
+
+    let mut l = Lexer::new(&input);
+    let mut items = vec![];
+    loop {
+        match l.next_token().map_err(Diagnostic::from)? {
+            (Token::Eof, _) => break,
+            (token, span) => items.push(Item::Token(token, span)),
+        }
+    }
+    items.extend(l.comments().iter().cloned().map(Item::Comment));
+    items.sort_by_key(|item| {
+        let span = item.span();
+        (span.line, span.colm)
+    });
+
+    let mut formatter = Formatter::make(&input);
+    for item in &items {
+        formatter.emit(item);
+    }
+    Ok(formatter.finish())
+}
+
+enum Item {
+    Token(Token, Span),
+    Comment(Comment),
+}
+
+impl Item {
+    fn span(&self) -> Span {
+        match self {
+            Self::Token(_, span) => *span,
+            Self::Comment(comment) => comment.span,
+        }
+    }
+}
+
+struct Formatter<'a> {
+    input: &'a [char],
+    /// Offset of the first char of every line of the input
+    line_starts: Vec<usize>,
+    lines: Vec<String>,
+    /// Blocks opened by `->` and not closed yet
+    depth: usize,
+    /// Source line the last emitted item ends at
+    last_line: Option<usize>,
+    /// Token emitted last on the current line, `None` at its start or after a comment
+    prev: Option<Token>,
+}
+
+impl<'a> Formatter<'a> {
+    fn make(input: &'a [char]) -> Self {
+        let mut line_starts = vec![0];
+        for (offset, x) in input.iter().enumerate() {
+            if *x == '\n' {
+                line_starts.push(offset + 1);
+            }
+        }
+        Self {
+            input,
+            line_starts,
+            lines: vec![],
+            depth: 0,
+            last_line: None,
+            prev: None,
+        }
+    }
+
+    fn text(&self, span: Span) -> String {
+        let start = self.line_starts[span.line - 1] + span.colm - 1;
+        self.input[start..start + span.len].iter().collect()
+    }
+
+    fn emit(&mut self, item: &Item) {
+        let span = item.span();
+        let starts_fn =
+            matches!(item, Item::Token(Token::FunctionDeclarator, _)) && self.depth == 0;
+        match self.last_line {
+            None => self.start_line(),
+            Some(last_line) if span.line > last_line => {
+                if span.line > last_line + 1 {
+                    self.lines.push(String::new());
+                }
+                self.start_line();
+            }
+            Some(_) if starts_fn => self.start_line(),
+            Some(_) => {}
+        }
+
+        let end_line = match item {
+            Item::Token(token, _) => {
+                if self.needs_space(token) {
+                    self.push(" ");
+                }
+                let text = self.text(span);
+                self.push(&text);
+                self.prev = Some(token.clone());
+                match token {
+                    Token::Arrow => self.depth += 1,
+                    Token::Semicolon => self.depth = self.depth.saturating_sub(1),
+                    _ => {}
+                }
+                span.line + text.matches('\n').count()
+            }
+            Item::Comment(comment) => {
+                if !self.current_line().trim().is_empty() {
+                    self.push(" ");
+                }
+                self.push(&comment.text);
+                self.prev = None;
+                span.line + comment.text.matches('\n').count()
+            }
+        };
+        self.last_line = Some(end_line);
+    }
+
+    fn needs_space(&self, token: &Token) -> bool {
+        let prev = match self.prev {
+            Some(ref prev) => prev,
+            None => return !self.current_line().trim().is_empty(),
+        };
+        match (prev, token) {
+            (Token::Dot | Token::Lparen, _) => false,
+            (Token::Arrow, Token::Semicolon) => true,
+            (_, Token::Semicolon | Token::Comma | Token::Rparen | Token::Lparen) => false,
+            _ => true,
+        }
+    }
+
+    fn start_line(&mut self) {
+        self.lines.push("  ".repeat(self.depth));
+        self.prev = None;
+    }
+
+    fn current_line(&self) -> &str {
+        self.lines.last().map(String::as_str).unwrap_or("")
+    }
+
+    fn push(&mut self, text: &str) {
+        match self.lines.last_mut() {
+            Some(line) => line.push_str(text),
+            None => self.lines.push(text.to_string()),
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut out = String::new();
+        for line in self.lines {
+            // only the end of a line is ours to trim, the lines that multi-line strings and block
+            // comments bring along are kept as they are
+            out.push_str(line.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format;
+
+    #[test]
+    fn ensure_blocks_are_indented_and_tokens_spaced() {
+        let source = "\
+f count( int i,int stop )   ->
+      i stop >   if ->return ;
+   i . write
+
+
+        i 1 + stop .count;
+f main->0 10 .count;";
+        let expected = "\
+f count(int i, int stop) ->
+  i stop > if -> return;
+  i .write
+
+  i 1 + stop .count;
+f main -> 0 10 .count;
+";
+        assert_eq!(Ok(expected.to_string()), format(source).map_err(|x| x.msg));
+        assert_eq!(
+            Ok(expected.to_string()),
+            format(expected).map_err(|x| x.msg)
+        );
+    }
+
+    #[test]
+    fn ensure_comments_are_preserved() {
+        let source = "\
+// Prints the odd numbers
+f main ->
+int i 0 =   // counter
+    while i 3 < -> /* step */ i i 1 + =
+  i .write;
+;";
+        let expected = "\
+// Prints the odd numbers
+f main ->
+  int i 0 = // counter
+  while i 3 < -> /* step */ i i 1 + =
+    i .write;
+  ;
+";
+        assert_eq!(Ok(expected.to_string()), format(source).map_err(|x| x.msg));
+    }
+
+    #[test]
+    fn ensure_multi_line_strings_are_kept_verbatim() {
+        let source = "f main ->\n\"a   \nb\" .write   /* c  \n d */;\n";
+        let expected = "f main ->\n  \"a   \nb\" .write /* c  \n d */ ;\n";
+        assert_eq!(Ok(expected.to_string()), format(source).map_err(|x| x.msg));
+        assert_eq!(
+            Ok(expected.to_string()),
+            format(expected).map_err(|x| x.msg)
+        );
+    }
+
+    #[test]
+    fn ensure_malformed_source_is_not_formatted() {
+        assert!(format("f main -> (;").is_err());
+    }
+}
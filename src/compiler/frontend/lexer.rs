@@ -0,0 +1,399 @@
+use super::{Comment, CommentKind, SyntaxError, Token};
+use crate::compiler::span::Span;
+
+const NULL_CHAR: char = '\0';
+
+pub struct Lexer<'a> {
+    input: &'a [char],
+    curr_char: char,
+    pos: usize,
+    read_pos: usize,
+    line: usize,
+    colm: usize,
+    comments: Vec<Comment>,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a [char]) -> Self {
+        let mut l = Lexer {
+            input,
+            curr_char: NULL_CHAR,
+            pos: 0,
+            read_pos: 0,
+            line: 1,
+            colm: 0,
+            comments: vec![],
+        };
+
+        l.read_char();
+
+        l
+    }
+
+    fn read_char(&mut self) {
+        if self.curr_char == '\n' {
+            self.line += 1;
+            self.colm = 1;
+        } else {
+            self.colm += 1;
+        }
+
+        if self.read_pos >= self.input.len() {
+            self.pos = self.read_pos;
+            self.curr_char = NULL_CHAR;
+            return;
+        }
+
+        self.curr_char = self.input[self.read_pos];
+        self.pos = self.read_pos;
+        self.read_pos += 1;
+    }
+
+    /// Returns the next token along with the span of source it was read from
+    pub fn next_token(&mut self) -> Result<(Token, Span), SyntaxError> {
+        self.skip_trivia()?;
+        let (line, colm, start) = (self.line, self.colm, self.pos);
+        let token = self.read_token(line, colm)?;
+        let len = self.pos - start;
+        Ok((token, Span::make(line, colm, len)))
+    }
+
+    fn read_token(&mut self, line: usize, colm: usize) -> Result<Token, SyntaxError> {
+        if self.eof() {
+            return Ok(Token::Eof);
+        }
+
+        let token = match self.curr_char {
+            '+' => Some(Token::Plus),
+            '*' => Some(Token::Asterisk),
+            '/' => Some(Token::Slash),
+            '%' => Some(Token::Percent),
+            '(' => Some(Token::Lparen),
+            ')' => Some(Token::Rparen),
+            ';' => Some(Token::Semicolon),
+            ',' => Some(Token::Comma),
+            '.' => Some(Token::Dot),
+            '>' => Some(self.either('=', Token::GratherOrEqual, Token::GratherThan)),
+            '<' => Some(self.either('=', Token::LessOrEqual, Token::LessThan)),
+            '=' => Some(self.either('=', Token::DoubleEqual, Token::Equal)),
+            '!' if self.next_char_is('=') => {
+                self.read_char();
+                Some(Token::NotEqual)
+            }
+            '-' => {
+                if self.next_char_is('>') {
+                    self.read_char();
+                    Some(Token::Arrow)
+                } else {
+                    Some(Token::Minus)
+                }
+            }
+            '"' => {
+                let token = self.read_string(line, colm);
+                self.read_char();
+                return token;
+            }
+            _ => None,
+        };
+
+        if let Some(token) = token {
+            self.read_char();
+            return Ok(token);
+        }
+
+        if self.curr_char.is_alphabetic() {
+            return Ok(self.read_identifier());
+        }
+
+        if self.curr_char.is_numeric() {
+            return self.read_number(line, colm);
+        }
+
+        let illegal = Token::Illegal(self.curr_char);
+        self.read_char();
+        Ok(illegal)
+    }
+
+    /// Consumes the next char and returns `matched` if it is `x`, otherwise returns `fallback`
+    fn either(&mut self, x: char, matched: Token, fallback: Token) -> Token {
+        if self.next_char_is(x) {
+            self.read_char();
+            matched
+        } else {
+            fallback
+        }
+    }
+
+    fn read_identifier(&mut self) -> Token {
+        let literal = self.chop_while(|x| x.is_alphanumeric() || x == '_');
+        // look for keywords
+        match literal.as_str() {
+            "f" => Token::FunctionDeclarator,
+            "int" => Token::TypeInteger,
+            "str" => Token::TypeString,
+            "if" => Token::If,
+            "else" => Token::Else,
+            "return" => Token::Return,
+            "while" => Token::While,
+            "break" => Token::Break,
+            "continue" => Token::Continue,
+            "bool" => Token::TypeBoolean,
+            "float" => Token::TypeFloat,
+            "true" => Token::True,
+            "false" => Token::False,
+            "and" => Token::And,
+            "or" => Token::Or,
+            "not" => Token::Not,
+            "itof" => Token::IntToFloat,
+            "ftoi" => Token::FloatToInt,
+            "dup" => Token::Dup,
+            "drop" => Token::Drop,
+            "swap" => Token::Swap,
+            "over" => Token::Over,
+            "rot" => Token::Rot,
+            _ => Token::Identifier(literal),
+        }
+    }
+
+    fn read_string(&mut self, line: usize, colm: usize) -> Result<Token, SyntaxError> {
+        self.read_char();
+        let literal = self.chop_while(|x| x != '"');
+        if self.curr_char != '"' {
+            return Err(SyntaxError::make(
+                "Unbalanced '\"'".to_string(),
+                Span::make(line, colm, 1),
+            ));
+        }
+        Ok(Token::String(literal))
+    }
+
+    /// Numbers with a `.` are floats, the others ints
+    fn read_number(&mut self, line: usize, colm: usize) -> Result<Token, SyntaxError> {
+        let literal = self.chop_while(|x| x.is_numeric() || x == '.');
+        let token = match literal.contains('.') {
+            true => literal.parse::<f64>().ok().map(Token::Float),
+            false => literal.parse::<i32>().ok().map(Token::Int),
+        };
+        token.ok_or_else(|| {
+            SyntaxError::make(
+                format!("Invalid number literal '{}'", literal),
+                Span::make(line, colm, literal.chars().count()),
+            )
+        })
+    }
+
+    /// Comments read so far, in the order they appear in the source
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.chop_while(|x| x.is_whitespace());
+    }
+
+    fn skip_trivia(&mut self) -> Result<(), SyntaxError> {
+        loop {
+            self.skip_whitespace();
+            if self.curr_char != '/' {
+                return Ok(());
+            }
+            let (line, colm, start) = (self.line, self.colm, self.pos);
+            let kind = if self.next_char_is('/') {
+                self.chop_while(|x| x != '\n');
+                CommentKind::Line
+            } else if self.next_char_is('*') {
+                self.read_block_comment(line, colm)?;
+                CommentKind::Block
+            } else {
+                return Ok(());
+            };
+            self.comments.push(Comment {
+                kind,
+                text: self.chop(start, self.pos),
+                span: Span::make(line, colm, self.pos - start),
+            });
+        }
+    }
+
+    fn read_block_comment(&mut self, line: usize, colm: usize) -> Result<(), SyntaxError> {
+        let mut depth = 0;
+        loop {
+            if self.eof() {
+                return Err(SyntaxError::make(
+                    "Unterminated block comment".to_string(),
+                    Span::make(line, colm, 2),
+                ));
+            }
+            if self.curr_char == '/' && self.next_char_is('*') {
+                depth += 1;
+                self.read_char();
+            } else if self.curr_char == '*' && self.next_char_is('/') {
+                depth -= 1;
+                self.read_char();
+            }
+            self.read_char();
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    fn chop_while<P>(&mut self, mut predicate: P) -> String
+    where
+        P: FnMut(char) -> bool,
+    {
+        let start = self.pos;
+        while !self.eof() && predicate(self.curr_char) {
+            self.read_char();
+        }
+        self.chop(start, self.pos)
+    }
+
+    fn chop(&self, begin: usize, end: usize) -> String {
+        self.input[begin..end].iter().collect::<String>()
+    }
+
+    fn eof(&mut self) -> bool {
+        self.curr_char == NULL_CHAR
+    }
+
+    fn next_char_is(&self, x: char) -> bool {
+        if self.read_pos >= self.input.len() {
+            return false;
+        }
+        self.input[self.read_pos] == x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lexer;
+    use crate::compiler::frontend::{CommentKind, SyntaxError, Token};
+    use crate::compiler::span::Span;
+
+    #[test]
+    fn missing_unquote_to_balance_string_literal() {
+        // "Hello world!
+        let input = "\"Hello world!";
+        let input = input.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        assert_eq!(
+            Err(SyntaxError::make(
+                "Unbalanced '\"'".to_string(),
+                Span::make(1, 1, 1)
+            )),
+            l.next_token()
+        );
+    }
+
+    #[test]
+    fn test_next_token() {
+        let input = "f main -> \"Hello, world!\" .write;";
+        let input = input.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut next_token = || l.next_token().map(|(token, _)| token);
+        assert_eq!(Ok(Token::FunctionDeclarator), next_token());
+        assert_eq!(Ok(Token::Identifier("main".to_string())), next_token());
+        assert_eq!(Ok(Token::Arrow), next_token());
+        assert_eq!(Ok(Token::String("Hello, world!".to_string())), next_token());
+        assert_eq!(Ok(Token::Dot), next_token());
+        assert_eq!(Ok(Token::Identifier("write".to_string())), next_token());
+        assert_eq!(Ok(Token::Semicolon), next_token());
+        assert_eq!(Ok(Token::Eof), next_token());
+    }
+
+    #[test]
+    fn ensure_tokens_carry_their_span() {
+        let input = "f main ->\n  \"Hi\" .write;";
+        let input = input.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut next_span = || l.next_token().map(|(_, span)| span).unwrap();
+        assert_eq!(Span::make(1, 1, 1), next_span());
+        assert_eq!(Span::make(1, 3, 4), next_span());
+        assert_eq!(Span::make(1, 8, 2), next_span());
+        assert_eq!(Span::make(2, 3, 4), next_span());
+        assert_eq!(Span::make(2, 8, 1), next_span());
+        assert_eq!(Span::make(2, 9, 5), next_span());
+        assert_eq!(Span::make(2, 14, 1), next_span());
+    }
+
+    #[test]
+    fn ensure_operators_are_well_tokenized() {
+        let input = "+ - * / % > >= < <= == != = ->";
+        let input = input.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut next_token = || l.next_token().map(|(token, _)| token);
+        assert_eq!(Ok(Token::Plus), next_token());
+        assert_eq!(Ok(Token::Minus), next_token());
+        assert_eq!(Ok(Token::Asterisk), next_token());
+        assert_eq!(Ok(Token::Slash), next_token());
+        assert_eq!(Ok(Token::Percent), next_token());
+        assert_eq!(Ok(Token::GratherThan), next_token());
+        assert_eq!(Ok(Token::GratherOrEqual), next_token());
+        assert_eq!(Ok(Token::LessThan), next_token());
+        assert_eq!(Ok(Token::LessOrEqual), next_token());
+        assert_eq!(Ok(Token::DoubleEqual), next_token());
+        assert_eq!(Ok(Token::NotEqual), next_token());
+        assert_eq!(Ok(Token::Equal), next_token());
+        assert_eq!(Ok(Token::Arrow), next_token());
+        assert_eq!(Ok(Token::Eof), next_token());
+    }
+
+    #[test]
+    fn ensure_numbers_are_ints_or_floats() {
+        let input = "42 2.5 float itof 1.2.3";
+        let input = input.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut next_token = || l.next_token().map(|(token, _)| token);
+        assert_eq!(Ok(Token::Int(42)), next_token());
+        assert_eq!(Ok(Token::Float(2.5)), next_token());
+        assert_eq!(Ok(Token::TypeFloat), next_token());
+        assert_eq!(Ok(Token::IntToFloat), next_token());
+        assert_eq!(
+            Err(SyntaxError::make(
+                "Invalid number literal '1.2.3'".to_string(),
+                Span::make(1, 19, 5)
+            )),
+            next_token()
+        );
+    }
+
+    #[test]
+    fn ensure_comments_are_kept_as_trivia() {
+        let input = "// main\nf main -> /* a /* nested */ one */ 1 / 2;";
+        let input = input.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut next_token = || l.next_token().map(|(token, _)| token);
+        assert_eq!(Ok(Token::FunctionDeclarator), next_token());
+        assert_eq!(Ok(Token::Identifier("main".to_string())), next_token());
+        assert_eq!(Ok(Token::Arrow), next_token());
+        assert_eq!(Ok(Token::Int(1)), next_token());
+        assert_eq!(Ok(Token::Slash), next_token());
+        assert_eq!(Ok(Token::Int(2)), next_token());
+        assert_eq!(Ok(Token::Semicolon), next_token());
+        assert_eq!(Ok(Token::Eof), next_token());
+
+        let comments = l.comments();
+        assert_eq!(2, comments.len());
+        assert_eq!(CommentKind::Line, comments[0].kind);
+        assert_eq!("// main", comments[0].text);
+        assert_eq!(Span::make(1, 1, 7), comments[0].span);
+        assert_eq!(CommentKind::Block, comments[1].kind);
+        assert_eq!("/* a /* nested */ one */", comments[1].text);
+        assert_eq!(Span::make(2, 11, 24), comments[1].span);
+    }
+
+    #[test]
+    fn ensure_unterminated_block_comment_is_reported() {
+        let input = "/* /* */";
+        let input = input.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        assert_eq!(
+            Err(SyntaxError::make(
+                "Unterminated block comment".to_string(),
+                Span::make(1, 1, 2)
+            )),
+            l.next_token()
+        );
+    }
+}
@@ -0,0 +1,161 @@
+pub mod lexer;
+pub mod parser;
+
+use core::fmt;
+
+use crate::compiler::diagnostics::Diagnostic;
+use crate::compiler::span::Span;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Illegal(char),
+    Eof,
+
+    Int(i32),
+    Float(f64),
+    String(String),
+    Identifier(String),
+
+    Plus,
+    Minus,
+    Asterisk,
+    Slash,
+    Percent,
+
+    Dot,
+    Arrow,
+    Comma,
+    Lparen,
+    Rparen,
+    Semicolon,
+    FunctionDeclarator,
+    If,
+    Else,
+    Return,
+    While,
+    Break,
+    Continue,
+
+    TypeInteger,
+    TypeString,
+    TypeBoolean,
+    TypeFloat,
+
+    True,
+    False,
+
+    GratherThan,
+    GratherOrEqual,
+    LessThan,
+    LessOrEqual,
+    DoubleEqual,
+    NotEqual,
+    Equal,
+
+    And,
+    Or,
+    Not,
+    IntToFloat,
+    FloatToInt,
+
+    Dup,
+    Drop,
+    Swap,
+    Over,
+    Rot,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Illegal(val) => write!(f, "[Illegal Token] {}", val),
+            Self::Eof => write!(f, "EOF"),
+            Self::Int(int) => write!(f, "{}", int),
+            Self::Float(float) => write!(f, "{:?}", float),
+            Self::String(str) => write!(f, "{}", str),
+            Self::Identifier(ident) => write!(f, "{}", ident),
+            Self::Plus => write!(f, "+"),
+            Self::Dot => write!(f, "."),
+            Self::Arrow => write!(f, "->"),
+            Self::Lparen => write!(f, "("),
+            Self::Rparen => write!(f, ")"),
+            Self::Semicolon => write!(f, ";"),
+            Self::Minus => write!(f, "-"),
+            Self::Asterisk => write!(f, "*"),
+            Self::Slash => write!(f, "/"),
+            Self::Percent => write!(f, "%"),
+            Self::FunctionDeclarator => write!(f, "[Function declaration] f"),
+            Self::TypeInteger => write!(f, "int"),
+            Self::TypeString => write!(f, "str"),
+            Self::TypeBoolean => write!(f, "bool"),
+            Self::TypeFloat => write!(f, "float"),
+            Self::Comma => write!(f, ","),
+            Self::If => write!(f, "if"),
+            Self::Else => write!(f, "else"),
+            Self::While => write!(f, "while"),
+            Self::Break => write!(f, "break"),
+            Self::Continue => write!(f, "continue"),
+            Self::GratherThan => write!(f, ">"),
+            Self::GratherOrEqual => write!(f, ">="),
+            Self::LessThan => write!(f, "<"),
+            Self::LessOrEqual => write!(f, "<="),
+            Self::DoubleEqual => write!(f, "=="),
+            Self::NotEqual => write!(f, "!="),
+            Self::And => write!(f, "and"),
+            Self::Or => write!(f, "or"),
+            Self::Not => write!(f, "not"),
+            Self::IntToFloat => write!(f, "itof"),
+            Self::FloatToInt => write!(f, "ftoi"),
+            Self::Dup => write!(f, "dup"),
+            Self::Drop => write!(f, "drop"),
+            Self::Swap => write!(f, "swap"),
+            Self::Over => write!(f, "over"),
+            Self::Rot => write!(f, "rot"),
+            Self::Return => write!(f, "return"),
+            Self::Equal => write!(f, "="),
+            Self::True => write!(f, "true"),
+            Self::False => write!(f, "false"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommentKind {
+    /// `// ...` up to the end of the line
+    Line,
+    /// `/* ... */`, may be nested
+    Block,
+}
+
+/// A comment kept aside by the lexer as trivia, `text` is the whole comment including its
+/// delimiters
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub kind: CommentKind,
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub msg: String,
+    pub span: Span,
+}
+
+impl SyntaxError {
+    pub fn make(msg: String, span: Span) -> Self {
+        Self { msg, span }
+    }
+}
+
+impl From<SyntaxError> for Diagnostic {
+    fn from(err: SyntaxError) -> Self {
+        Diagnostic::make("[Syntax Error]".to_string(), err.msg, Some(err.span))
+    }
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.msg, self.span)
+    }
+}
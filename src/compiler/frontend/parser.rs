@@ -1,15 +1,18 @@
-use bug::Type;
+use crate::Type;
 
 use super::lexer::Lexer;
-use super::Token;
-use crate::ast::*;
+use super::{SyntaxError, Token};
+use crate::compiler::ast::*;
+use crate::compiler::span::Span;
 
-type ParserError = String;
+type ParserError = SyntaxError;
 
 pub struct Parser<'a> {
     lexer: &'a mut Lexer<'a>,
     curr_token: Token,
+    curr_span: Span,
     next_token: Token,
+    next_span: Span,
 }
 
 impl<'a> Parser<'a> {
@@ -17,19 +20,19 @@ impl<'a> Parser<'a> {
         Self {
             lexer,
             curr_token: Token::Eof,
+            curr_span: Span::default(),
             next_token: Token::Eof,
+            next_span: Span::default(),
         }
     }
 
     fn bump(&mut self) -> Result<(), ParserError> {
         self.curr_token = self.next_token.clone();
-        match self.lexer.next_token() {
-            Ok(next_token) => {
-                self.next_token = next_token;
-                Ok(())
-            }
-            Err(err) => Err(err),
-        }
+        self.curr_span = self.next_span;
+        let (next_token, next_span) = self.lexer.next_token()?;
+        self.next_token = next_token;
+        self.next_span = next_span;
+        Ok(())
     }
 
     fn bump_expected(&mut self, token: Token) -> Result<(), ParserError> {
@@ -37,19 +40,29 @@ impl<'a> Parser<'a> {
             self.bump()?;
             Ok(())
         } else {
-            Err(format!("Expected {} but got {}", token, self.curr_token))
+            Err(self.error(format!("Expected {} but got {}", token, self.curr_token)))
         }
     }
 
+    fn error(&self, msg: String) -> ParserError {
+        SyntaxError::make(msg, self.curr_span)
+    }
+
+    /// Whether parsing stopped at the end of the input, an error there means the source is
+    /// incomplete rather than wrong
+    pub fn is_at_eof(&self) -> bool {
+        self.curr_token == Token::Eof
+    }
+
     fn is_curr_token(&self, token: Token) -> bool {
         self.curr_token == token
     }
 
-    pub fn parse(&mut self) -> Result<AST, ParserError> {
+    pub fn parse(&mut self) -> Result<Ast, ParserError> {
         self.bump()?;
         self.bump()?;
 
-        let mut ast: AST = vec![];
+        let mut ast: Ast = vec![];
 
         while self.curr_token != Token::Eof {
             let stmt = self.parse_statement()?;
@@ -66,37 +79,41 @@ impl<'a> Parser<'a> {
     fn parse_statement(&mut self) -> Result<Statement, ParserError> {
         match self.curr_token {
             Token::If => self.parse_if_statement(),
-            Token::Equal => Ok(Statement::Assignment(None)),
+            Token::While => self.parse_while_statement(),
+            Token::Break => Ok(Statement::Break(self.curr_span)),
+            Token::Continue => Ok(Statement::Continue(self.curr_span)),
+            Token::Equal => Ok(Statement::Assignment(None, self.curr_span)),
             Token::FunctionDeclarator => self.parse_function_declaration(),
-            Token::TypeString | Token::TypeInteger | Token::TypeBoolean => {
+            Token::TypeString | Token::TypeInteger | Token::TypeBoolean | Token::TypeFloat => {
                 self.parse_var_declaration()
             }
-            _ => match self.parse_expression() {
-                Ok(expression) => Ok(Statement::Expression(expression)),
-                Err(err) => Err(err),
-            },
+            _ => Ok(Statement::Expression(self.parse_expression()?)),
         }
     }
 
     fn parse_var_declaration(&mut self) -> Result<Statement, ParserError> {
+        let span = self.curr_span;
         let var_type = match &self.curr_token {
             Token::TypeString => Type::String,
             Token::TypeInteger => Type::Integer,
             Token::TypeBoolean => Type::Boolean,
-            x => return Err(format!("Cannot declare variable with prefix '{}'", x)),
+            Token::TypeFloat => Type::Float,
+            x => return Err(self.error(format!("Cannot declare variable with prefix '{}'", x))),
         };
         self.bump()?;
         let var_name = match &self.curr_token {
             Token::Identifier(ref name) => name.clone(),
-            x => return Err(format!("Cannot declare variable with name '{}'", x)),
+            x => return Err(self.error(format!("Cannot declare variable with name '{}'", x))),
         };
         Ok(Statement::VariableDeclaration(VariableDeclaration {
             type_: var_type,
             name: var_name,
+            span: span.merge(self.curr_span),
         }))
     }
 
     fn parse_if_statement(&mut self) -> Result<Statement, ParserError> {
+        let span = self.curr_span;
         self.bump_expected(Token::If)?;
         self.bump_expected(Token::Arrow)?;
         let if_block = self.parse_block_statement()?;
@@ -105,17 +122,34 @@ impl<'a> Parser<'a> {
             self.bump_expected(Token::Else)?;
             self.bump_expected(Token::Arrow)?;
             let else_block = self.parse_block_statement()?;
-            Ok(Statement::If(if_block, Some(else_block)))
+            Ok(Statement::If(if_block, Some(else_block), span))
         } else {
-            Ok(Statement::If(if_block, None))
+            Ok(Statement::If(if_block, None, span))
+        }
+    }
+
+    fn parse_while_statement(&mut self) -> Result<Statement, ParserError> {
+        let span = self.curr_span;
+        self.bump_expected(Token::While)?;
+        let mut condition: BlockStatement = vec![];
+        while !self.is_curr_token(Token::Arrow) {
+            if self.is_curr_token(Token::Eof) {
+                return Err(self.error("Expected -> after 'while' condition".to_string()));
+            }
+            condition.push(self.parse_statement()?);
+            self.bump()?;
         }
+        self.bump_expected(Token::Arrow)?;
+        let body = self.parse_block_statement()?;
+        Ok(Statement::While(condition, body, span))
     }
 
     fn parse_function_declaration(&mut self) -> Result<Statement, ParserError> {
         self.bump_expected(Token::FunctionDeclarator)?;
+        let span = self.curr_span;
         let name = match self.curr_token {
             Token::Identifier(ref name) => name.clone(),
-            _ => return Err(format!("'f' must follow an identifier")),
+            _ => return Err(self.error("'f' must follow an identifier".to_string())),
         };
         self.bump()?;
 
@@ -131,6 +165,7 @@ impl<'a> Parser<'a> {
             params,
             return_type,
             body,
+            span,
         }))
     }
 
@@ -140,11 +175,12 @@ impl<'a> Parser<'a> {
             Token::TypeInteger => Type::Integer,
             Token::TypeString => Type::String,
             Token::TypeBoolean => Type::Boolean,
+            Token::TypeFloat => Type::Float,
             _ => {
-                return Err(format!(
+                return Err(self.error(format!(
                     "Expected return type annotation, but provided '{}'",
                     self.curr_token
-                ))
+                )))
             }
         };
 
@@ -162,36 +198,39 @@ impl<'a> Parser<'a> {
         }
         self.bump_expected(Token::Lparen)?;
         while self.curr_token != Token::Rparen {
+            let span = self.curr_span;
             let param_type = match self.curr_token {
                 Token::TypeString => Type::String,
                 Token::TypeInteger => Type::Integer,
                 Token::TypeBoolean => Type::Boolean,
+                Token::TypeFloat => Type::Float,
                 _ => {
-                    return Err(format!(
+                    return Err(self.error(format!(
                         "Expected param type, but provided '{}'",
                         self.curr_token
-                    ))
+                    )))
                 }
             };
             self.bump()?;
             let param_name = match self.curr_token {
                 Token::Identifier(ref name) => name.clone(),
                 _ => {
-                    return Err(format!(
+                    return Err(self.error(format!(
                         "Expected param name, but provided '{}'",
                         self.curr_token
-                    ))
+                    )))
                 }
             };
-            self.bump()?;
             params.push(FnParam {
                 name: param_name,
                 type_: param_type,
+                span: span.merge(self.curr_span),
             });
+            self.bump()?;
             match self.curr_token {
                 Token::Rparen => break,
                 Token::Comma => self.bump()?,
-                _ => return Err(format!("Function params must be separated by ','")),
+                _ => return Err(self.error("Function params must be separated by ','".to_string())),
             };
         }
         self.bump_expected(Token::Rparen)?;
@@ -208,25 +247,52 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_expression(&mut self) -> Result<Expression, ParserError> {
+        let span = self.curr_span;
         match self.curr_token {
-            Token::Int(x) => Ok(Expression::Literal(Literal::Int(x))),
-            Token::String(ref x) => Ok(Expression::Literal(Literal::String(x.clone()))),
-            Token::Identifier(ref identifier) => Ok(Expression::Identifier(identifier.clone())),
-            Token::True => Ok(Expression::Literal(Literal::Boolean(true))),
-            Token::False => Ok(Expression::Literal(Literal::Boolean(false))),
+            Token::Int(x) => Ok(Expression::Literal(Literal::Int(x), span)),
+            Token::Float(x) => Ok(Expression::Literal(Literal::Float(x), span)),
+            Token::String(ref x) => Ok(Expression::Literal(Literal::String(x.clone()), span)),
+            Token::Identifier(ref identifier) => {
+                Ok(Expression::Identifier(identifier.clone(), span))
+            }
+            Token::True => Ok(Expression::Literal(Literal::Boolean(true), span)),
+            Token::False => Ok(Expression::Literal(Literal::Boolean(false), span)),
             Token::Dot => self.parse_function_call(),
-            Token::Plus => Ok(Expression::BinaryOp(BinaryOp::Plus(None))),
-            Token::GratherThan => Ok(Expression::BinaryOp(BinaryOp::GratherThan(None))),
-            Token::Return => Ok(Expression::Return(None)),
-            _ => return Err(format!("Unexpected expression: {}", self.curr_token)),
+            Token::Plus => Ok(Expression::BinaryOp(BinaryOp::Plus(None), span)),
+            Token::Minus => Ok(Expression::BinaryOp(BinaryOp::Minus(None), span)),
+            Token::Asterisk => Ok(Expression::BinaryOp(BinaryOp::Multiply(None), span)),
+            Token::Slash => Ok(Expression::BinaryOp(BinaryOp::Divide(None), span)),
+            Token::Percent => Ok(Expression::BinaryOp(BinaryOp::Modulo(None), span)),
+            Token::GratherThan => Ok(Expression::BinaryOp(BinaryOp::GratherThan(None), span)),
+            Token::GratherOrEqual => Ok(Expression::BinaryOp(BinaryOp::GratherOrEqual(None), span)),
+            Token::LessThan => Ok(Expression::BinaryOp(BinaryOp::LessThan(None), span)),
+            Token::LessOrEqual => Ok(Expression::BinaryOp(BinaryOp::LessOrEqual(None), span)),
+            Token::DoubleEqual => Ok(Expression::BinaryOp(BinaryOp::Equal(None), span)),
+            Token::NotEqual => Ok(Expression::BinaryOp(BinaryOp::NotEqual(None), span)),
+            Token::And => Ok(Expression::BinaryOp(BinaryOp::And(None), span)),
+            Token::Or => Ok(Expression::BinaryOp(BinaryOp::Or(None), span)),
+            Token::Not => Ok(Expression::UnaryOp(UnaryOp::Not, span)),
+            Token::IntToFloat => Ok(Expression::UnaryOp(UnaryOp::IntToFloat, span)),
+            Token::FloatToInt => Ok(Expression::UnaryOp(UnaryOp::FloatToInt, span)),
+            Token::Dup => Ok(Expression::StackOp(StackOp::Dup, span)),
+            Token::Drop => Ok(Expression::StackOp(StackOp::Drop, span)),
+            Token::Swap => Ok(Expression::StackOp(StackOp::Swap, span)),
+            Token::Over => Ok(Expression::StackOp(StackOp::Over, span)),
+            Token::Rot => Ok(Expression::StackOp(StackOp::Rot, span)),
+            Token::Return => Ok(Expression::Return(None, span)),
+            _ => Err(self.error(format!("Unexpected expression: {}", self.curr_token))),
         }
     }
 
     fn parse_function_call(&mut self) -> Result<Expression, ParserError> {
+        let span = self.curr_span;
         self.bump_expected(Token::Dot)?;
         match &self.curr_token {
-            Token::Identifier(ref fn_name) => Ok(Expression::FunctionCall(fn_name.clone())),
-            _ => Err(format!("Missing function's name")),
+            Token::Identifier(ref fn_name) => Ok(Expression::FunctionCall(
+                fn_name.clone(),
+                span.merge(self.curr_span),
+            )),
+            _ => Err(self.error("Missing function's name".to_string())),
         }
     }
 }
@@ -234,11 +300,12 @@ impl<'a> Parser<'a> {
 #[cfg(test)]
 mod tests {
     use super::{Parser, Statement};
-    use crate::frontend::{
+    use crate::compiler::frontend::{
         lexer::Lexer,
-        parser::{BinaryOp, Expression, FnParam, Literal},
+        parser::{BinaryOp, Expression, FnParam, Literal, UnaryOp},
     };
-    use bug::Type;
+    use crate::compiler::span::Span;
+    use crate::Type;
 
     #[test]
     fn missing_function_name_on_call() {
@@ -281,9 +348,10 @@ mod tests {
         );
         assert_eq!(3, fn_decl.body.len(), "Function body must has 3 statements");
 
-        let expected_param_list = vec![FnParam {
+        let expected_param_list = [FnParam {
             name: "x".to_string(),
             type_: Type::Integer,
+            span: Span::make(1, 10, 5),
         }];
 
         assert_eq!(expected_param_list.len(), fn_decl.params.len());
@@ -293,21 +361,21 @@ mod tests {
         );
 
         match fn_decl.body[0].clone() {
-            Statement::Expression(Expression::Identifier(x)) => {
+            Statement::Expression(Expression::Identifier(x, _)) => {
                 assert_eq!("x".to_string(), x)
             }
             _ => panic!("First statement must be Identifier 'x'"),
         }
 
         match fn_decl.body[1].clone() {
-            Statement::Expression(Expression::Identifier(x)) => {
+            Statement::Expression(Expression::Identifier(x, _)) => {
                 assert_eq!("x".to_string(), x)
             }
             _ => panic!("Second statement must be Identifier 'x'"),
         }
 
         match fn_decl.body[2].clone() {
-            Statement::Expression(Expression::BinaryOp(BinaryOp::Plus(_))) => {}
+            Statement::Expression(Expression::BinaryOp(BinaryOp::Plus(_), _)) => {}
             _ => panic!("Last statement must be BinaryOp 'plus'"),
         }
     }
@@ -328,8 +396,9 @@ mod tests {
         assert_eq!(1, ast.len());
 
         match ast[0].clone() {
-            Statement::Expression(Expression::FunctionCall(callee)) => {
-                assert_eq!("sum", &callee)
+            Statement::Expression(Expression::FunctionCall(callee, span)) => {
+                assert_eq!("sum", &callee);
+                assert_eq!(Span::make(1, 1, 4), span);
             }
             x => panic!("Expected function call expression, but got {:#?}", x),
         }
@@ -351,7 +420,7 @@ mod tests {
         assert_eq!(1, ast.len());
 
         match ast[0].clone() {
-            Statement::Expression(Expression::Literal(Literal::Int(69))) => {}
+            Statement::Expression(Expression::Literal(Literal::Int(69), _)) => {}
             _ => panic!("Expected int literal expression '69'"),
         }
     }
@@ -372,8 +441,64 @@ mod tests {
         assert_eq!(1, ast.len());
 
         match ast[0].clone() {
-            Statement::Expression(Expression::Literal(Literal::String(x))) => assert_eq!("Hi", &x),
+            Statement::Expression(Expression::Literal(Literal::String(x), _)) => {
+                assert_eq!("Hi", &x)
+            }
             _ => panic!("Expected int literal expression '69'"),
         }
     }
+
+    #[test]
+    fn ensure_logical_operators_are_well_formed() {
+        let input = "true false and not";
+
+        let input = input.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut p = Parser::new(&mut l);
+
+        let ast = match p.parse() {
+            Ok(ast) => ast,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(4, ast.len());
+
+        match ast[2].clone() {
+            Statement::Expression(Expression::BinaryOp(BinaryOp::And(_), _)) => {}
+            x => panic!("Expected 'and' operation, but got {:#?}", x),
+        }
+
+        match ast[3].clone() {
+            Statement::Expression(Expression::UnaryOp(UnaryOp::Not, _)) => {}
+            x => panic!("Expected 'not' operation, but got {:#?}", x),
+        }
+    }
+
+    #[test]
+    fn ensure_while_statement_is_well_formed() {
+        let input = "while i 10 < -> i .write break;";
+
+        let input = input.chars().collect::<Vec<char>>();
+        let mut l = Lexer::new(&input);
+        let mut p = Parser::new(&mut l);
+
+        let ast = match p.parse() {
+            Ok(ast) => ast,
+            Err(e) => panic!("{}", e),
+        };
+
+        assert_eq!(1, ast.len());
+
+        match ast[0].clone() {
+            Statement::While(condition, body, _) => {
+                assert_eq!(3, condition.len(), "Condition must has 3 statements");
+                assert_eq!(3, body.len(), "Body must has 3 statements");
+                match body[2] {
+                    Statement::Break(_) => {}
+                    ref x => panic!("Expected break statement, but got {:#?}", x),
+                }
+            }
+            x => panic!("Expected while statement, but got {:#?}", x),
+        }
+    }
 }
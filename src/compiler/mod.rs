@@ -0,0 +1,47 @@
+pub mod analysis;
+pub mod ast;
+pub mod codegeneration;
+pub mod diagnostics;
+pub mod document;
+pub mod formatter;
+pub mod frontend;
+pub mod span;
+
+use crate::stdlib::{list_native_fns, Natives};
+use crate::Program;
+use analysis::Analyser;
+use ast::Ast;
+use codegeneration::CodeGenerator;
+use diagnostics::Diagnostic;
+use frontend::{lexer::Lexer, parser::Parser};
+
+/// Runs the whole pipeline over `source` and returns the program ready to be executed
+pub fn compile(source: &str) -> Result<Program, Vec<Diagnostic>> {
+    compile_with(source, &list_native_fns())
+}
+
+/// Same as `compile` but calls are checked against the natives of `natives`
+pub fn compile_with(source: &str, natives: &Natives) -> Result<Program, Vec<Diagnostic>> {
+    let ast = check_with(source, natives)?;
+    let mut generator = CodeGenerator::make();
+    Ok(generator.gen(ast))
+}
+
+/// Parses and analyses `source` without generating any code
+pub fn check(source: &str) -> Result<Ast, Vec<Diagnostic>> {
+    check_with(source, &list_native_fns())
+}
+
+/// Same as `check` but calls are checked against the natives of `natives`
+pub fn check_with(source: &str, natives: &Natives) -> Result<Ast, Vec<Diagnostic>> {
+    let input = source.chars().collect::<Vec<char>>();
+    let mut l = Lexer::new(&input);
+    let mut p = Parser::new(&mut l);
+    let mut ast = p.parse().map_err(|err| vec![err.into()])?;
+
+    let mut analiser = Analyser::make_with(natives);
+    analiser
+        .analyse(&mut ast)
+        .map_err(|errors| errors.into_iter().map(Diagnostic::from).collect::<Vec<_>>())?;
+    Ok(ast)
+}
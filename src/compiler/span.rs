@@ -0,0 +1,30 @@
+use core::fmt;
+
+/// Location of a piece of source code, `line` and `colm` are 1-based and `len` is the amount of
+/// chars covered by the span starting at `colm`
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Span {
+    pub line: usize,
+    pub colm: usize,
+    pub len: usize,
+}
+
+impl Span {
+    pub fn make(line: usize, colm: usize, len: usize) -> Self {
+        Self { line, colm, len }
+    }
+
+    /// Extends the span up to the end of `other`, spans across multiple lines are kept as is
+    pub fn merge(self, other: Span) -> Self {
+        if self.line != other.line || other.colm < self.colm {
+            return self;
+        }
+        Self::make(self.line, self.colm, other.colm + other.len - self.colm)
+    }
+}
+
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.colm)
+    }
+}
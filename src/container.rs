@@ -0,0 +1,309 @@
+use core::fmt;
+
+use bincode::Options;
+
+use crate::Program;
+
+/// Every compiled file starts with these bytes
+pub const MAGIC: &[u8; 4] = b"BUGB";
+/// Version of the container layout and of the serialized `Program`, must be bumped whenever the
+/// serialization of `Program` changes (e.g. a new `Opcode`) so that stale files are rejected
+pub const FORMAT_VERSION: u16 = 4;
+/// Version of the toolkit writing the files
+pub const COMPILER_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SectionKind {
+    Program,
+}
+
+impl SectionKind {
+    fn to_byte(self) -> u8 {
+        match self {
+            Self::Program => 1,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            1 => Some(Self::Program),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    /// `None` for kinds written by a newer compiler, those are skipped
+    pub kind: Option<SectionKind>,
+    pub offset: usize,
+    pub len: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Header {
+    pub format_version: u16,
+    pub compiler_version: String,
+    pub sections: Vec<Section>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContainerError {
+    Truncated,
+    BadMagic,
+    UnsupportedVersion {
+        found: u16,
+        compiler_version: String,
+    },
+    ChecksumMismatch,
+    MissingSection(SectionKind),
+    Corrupted(String),
+}
+
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "File is truncated"),
+            Self::BadMagic => write!(f, "Not a bug program, bad magic number"),
+            Self::UnsupportedVersion {
+                found,
+                compiler_version,
+            } => write!(
+                f,
+                "Program was compiled by bugc {} with format version {} but this virtual machine only supports version {}, recompile it",
+                compiler_version, found, FORMAT_VERSION
+            ),
+            Self::ChecksumMismatch => write!(f, "Checksum mismatch, the file is corrupted"),
+            Self::MissingSection(kind) => write!(f, "Missing {:?} section", kind),
+            Self::Corrupted(msg) => write!(f, "Couldn't deserialize program: {}", msg),
+        }
+    }
+}
+
+/// Serializes `program` wrapped in a container:
+///
+/// ```text
+/// magic            4 bytes "BUGB"
+/// format version   u16
+/// compiler version u8 length followed by utf-8 bytes
+/// section count    u16
+/// section table    per section: kind u8, offset u32, length u32
+/// sections         the bytes of each section
+/// checksum         u32 CRC-32 of everything above
+/// ```
+///
+/// All integers are little-endian and offsets are relative to the start of the file
+pub fn encode(program: &Program) -> Vec<u8> {
+    pack(&[(SectionKind::Program, bincode::serialize(program).unwrap())])
+}
+
+fn pack(sections: &[(SectionKind, Vec<u8>)]) -> Vec<u8> {
+    let mut out: Vec<u8> = vec![];
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    out.push(COMPILER_VERSION.len() as u8);
+    out.extend_from_slice(COMPILER_VERSION.as_bytes());
+    out.extend_from_slice(&(sections.len() as u16).to_le_bytes());
+
+    let mut offset = out.len() + sections.len() * 9;
+    for (kind, bytes) in sections {
+        out.push(kind.to_byte());
+        out.extend_from_slice(&(offset as u32).to_le_bytes());
+        out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        offset += bytes.len();
+    }
+    for (_, bytes) in sections {
+        out.extend_from_slice(bytes);
+    }
+
+    let checksum = crc32(&out);
+    out.extend_from_slice(&checksum.to_le_bytes());
+    out
+}
+
+/// Reads the header of a container without validating the checksum nor the sections
+pub fn read_header(bytes: &[u8]) -> Result<Header, ContainerError> {
+    if !bytes.starts_with(MAGIC) {
+        return Err(ContainerError::BadMagic);
+    }
+    let mut reader = Reader {
+        bytes,
+        pos: MAGIC.len(),
+    };
+    let format_version = reader.u16()?;
+    let version_len = reader.take(1)?[0] as usize;
+    let compiler_version = String::from_utf8_lossy(reader.take(version_len)?).to_string();
+
+    if format_version != FORMAT_VERSION {
+        return Err(ContainerError::UnsupportedVersion {
+            found: format_version,
+            compiler_version,
+        });
+    }
+
+    let section_count = reader.u16()?;
+    let mut sections = vec![];
+    for _ in 0..section_count {
+        let kind = SectionKind::from_byte(reader.take(1)?[0]);
+        let offset = reader.u32()? as usize;
+        let len = reader.u32()? as usize;
+        sections.push(Section { kind, offset, len });
+    }
+
+    Ok(Header {
+        format_version,
+        compiler_version,
+        sections,
+    })
+}
+
+/// Validates the container and deserializes the program it holds
+pub fn decode(bytes: &[u8]) -> Result<Program, ContainerError> {
+    let header = read_header(bytes)?;
+
+    let body_len = bytes
+        .len()
+        .checked_sub(4)
+        .ok_or(ContainerError::Truncated)?;
+    let (body, checksum) = bytes.split_at(body_len);
+    if crc32(body) != u32::from_le_bytes(checksum.try_into().unwrap()) {
+        return Err(ContainerError::ChecksumMismatch);
+    }
+
+    let section = header
+        .sections
+        .iter()
+        .find(|section| section.kind == Some(SectionKind::Program))
+        .ok_or(ContainerError::MissingSection(SectionKind::Program))?;
+    let section_bytes = section
+        .offset
+        .checked_add(section.len)
+        .and_then(|end| body.get(section.offset..end))
+        .ok_or(ContainerError::Truncated)?;
+
+    // the lengths inside of the payload can't be trusted, bincode only holds reads to the limit
+    // when deserializing from a reader so nothing is allocated past the end of the payload
+    bincode::options()
+        .with_fixint_encoding()
+        .with_limit(section_bytes.len() as u64)
+        .deserialize_from(section_bytes)
+        .map_err(|err| ContainerError::Corrupted(err.to_string()))
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], ContainerError> {
+        let slice = self
+            .bytes
+            .get(self.pos..self.pos + len)
+            .ok_or(ContainerError::Truncated)?;
+        self.pos += len;
+        Ok(slice)
+    }
+
+    fn u16(&mut self) -> Result<u16, ContainerError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, ContainerError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+}
+
+/// CRC-32 (IEEE 802.3) of `bytes`
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytecode::Opcode;
+    use crate::vm::testing;
+
+    fn program() -> Program {
+        testing::program(vec![("main", 0, vec![Opcode::Return])])
+    }
+
+    #[test]
+    fn ensure_program_survives_a_roundtrip() {
+        let bytes = encode(&program());
+        let header = read_header(&bytes).unwrap();
+        assert_eq!(FORMAT_VERSION, header.format_version);
+        assert_eq!(COMPILER_VERSION, header.compiler_version);
+        assert_eq!(1, header.sections.len());
+        let decoded = decode(&bytes).unwrap();
+        assert_eq!(Some(0), decoded.fn_index("main"));
+    }
+
+    #[test]
+    fn ensure_corrupted_or_foreign_files_are_rejected() {
+        let bytes = encode(&program());
+
+        let mut flipped = bytes.clone();
+        let last_section_byte = flipped.len() - 5;
+        flipped[last_section_byte] ^= 0xFF;
+        assert_eq!(
+            Err(ContainerError::ChecksumMismatch),
+            decode(&flipped).map(|_| ())
+        );
+
+        let mut stale = bytes.clone();
+        stale[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        match decode(&stale) {
+            Err(ContainerError::UnsupportedVersion { found, .. }) => {
+                assert_eq!(FORMAT_VERSION + 1, found)
+            }
+            x => panic!(
+                "Expected unsupported version error, but got {:?}",
+                x.map(|_| ())
+            ),
+        }
+
+        assert_eq!(
+            Err(ContainerError::BadMagic),
+            decode(b"\x7fELF....").map(|_| ())
+        );
+        assert_eq!(
+            Err(ContainerError::Truncated),
+            decode(&bytes[..8]).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn ensure_payloads_are_read_within_their_length() {
+        let payload = bincode::serialize(&program()).unwrap();
+
+        let truncated = pack(&[(SectionKind::Program, payload[..payload.len() / 2].to_vec())]);
+        assert!(matches!(
+            decode(&truncated),
+            Err(ContainerError::Corrupted(_))
+        ));
+
+        // the name of `main` claims more bytes than the payload could ever hold, it follows the
+        // lengths of the pool and of the functions
+        let mut oversized = payload.clone();
+        oversized[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+        match decode(&pack(&[(SectionKind::Program, oversized)])) {
+            Err(ContainerError::Corrupted(msg)) => assert!(msg.contains("size limit"), "{}", msg),
+            x => panic!("Expected corrupted error, but got {:?}", x.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn ensure_crc32_matches_reference() {
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+    }
+}
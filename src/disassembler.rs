@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+
+use crate::bytecode::{ByteCodeStream, Opcode};
+use crate::{DefinedFn, Object, PoolEntry, Program};
+
+/// Renders a human readable listing of `program`: the constant pool and the natives it uses
+/// followed by each function in index order, jump targets are replaced with labels
+pub fn disassemble(program: &Program) -> String {
+    let mut out = String::from("pool:\n");
+    for (index, entry) in program.pool.entries.iter().enumerate() {
+        out.push_str(&format!("  #{} = {}\n", index, render_pool_entry(entry)));
+    }
+    out.push_str("natives:\n");
+    for (index, name) in program.natives.iter().enumerate() {
+        out.push_str(&format!("  #{} = {}\n", index, name));
+    }
+
+    for (index, defined_fn) in program.fns.iter().enumerate() {
+        out.push('\n');
+        out.push_str(&disassemble_fn(index, defined_fn, program));
+    }
+    out
+}
+
+fn disassemble_fn(index: usize, defined_fn: &DefinedFn, program: &Program) -> String {
+    let mut out = format!(
+        "f {} #{} (arity: {}, max_locals: {}):\n",
+        defined_fn.name, index, defined_fn.arity, defined_fn.max_locals
+    );
+    let annotate = |opcode: &Opcode, comment: Option<String>| match comment {
+        Some(comment) => format!("{:<20}; {}", opcode.to_string(), comment),
+        None => format!("{:<20}; <invalid index>", opcode.to_string()),
+    };
+    let labels = collect_labels(&defined_fn.code);
+    let label = |offset: &usize| match labels.get(offset) {
+        Some(label) => label.clone(),
+        None => format!("<invalid {}>", offset),
+    };
+
+    for (offset, opcode) in defined_fn.code.iter().enumerate() {
+        if let Some(label) = labels.get(&offset) {
+            out.push_str(&format!("  {}:\n", label));
+        }
+        let instr = match opcode {
+            Opcode::Jump(target) => format!("[jump] {}", label(target)),
+            Opcode::JumpIfFalse(target) => format!("[jumpiffalse] {}", label(target)),
+            Opcode::Ldc(index) => annotate(
+                opcode,
+                program.pool.entries.get(*index).map(render_pool_entry),
+            ),
+            Opcode::Invoke(index) | Opcode::TailInvoke(index) => annotate(
+                opcode,
+                program.fns.get(*index).map(|callee| callee.name.clone()),
+            ),
+            Opcode::InvokeNative(index) => annotate(opcode, program.natives.get(*index).cloned()),
+            _ => opcode.to_string(),
+        };
+        out.push_str(&format!("    {:>4}  {}\n", offset, instr));
+    }
+
+    // jumps may target the offset right after the last instruction
+    if let Some(label) = labels.get(&defined_fn.code.get_pos()) {
+        out.push_str(&format!("  {}:\n", label));
+    }
+    out
+}
+
+/// Names every jump target of `code` as `L0`, `L1`, ... in order of appearance
+fn collect_labels(code: &ByteCodeStream) -> BTreeMap<usize, String> {
+    let mut labels = BTreeMap::new();
+    for opcode in code.iter() {
+        if let Opcode::Jump(target) | Opcode::JumpIfFalse(target) = opcode {
+            if *target <= code.get_pos() {
+                labels.insert(*target, String::new());
+            }
+        }
+    }
+    for (index, label) in labels.values_mut().enumerate() {
+        *label = format!("L{}", index);
+    }
+    labels
+}
+
+fn render_pool_entry(entry: &PoolEntry) -> String {
+    match entry {
+        PoolEntry::Object(Object::String(x)) => format!("{:?}", x),
+        PoolEntry::Object(object) => object.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::disassemble;
+    use crate::bytecode::{ByteCodeStream, Opcode, PushOperand};
+    use crate::{DefinedFn, LineTable, Object, Pool, PoolEntry, Program};
+
+    #[test]
+    fn ensure_jump_targets_are_labeled() {
+        let mut pool = Pool::make();
+        pool.append(PoolEntry::Object(Object::String("big".to_string())));
+        let code = ByteCodeStream::from(vec![
+            Opcode::Push(PushOperand::Boolean(true)),
+            Opcode::JumpIfFalse(4),
+            Opcode::Ldc(0),
+            Opcode::InvokeNative(0),
+            Opcode::Return,
+        ]);
+        let fns = vec![DefinedFn {
+            name: "main".to_string(),
+            arity: 0,
+            code,
+            max_locals: 0,
+            lines: LineTable::make(),
+        }];
+
+        let listing = disassemble(&Program {
+            pool,
+            fns,
+            natives: vec!["write".to_string()],
+        });
+        let expected = "\
+pool:
+  #0 = \"big\"
+natives:
+  #0 = write
+
+f main #0 (arity: 0, max_locals: 0):
+       0  [bipush] true
+       1  [jumpiffalse] L0
+       2  [ldc] 0             ; \"big\"
+       3  [invokenative] 0    ; write
+  L0:
+       4  [return]
+";
+        assert_eq!(expected, listing);
+    }
+}
@@ -1,9 +1,12 @@
 pub mod bytecode;
+pub mod compiler;
+pub mod container;
+pub mod disassembler;
 pub mod stdlib;
 pub mod utils;
+pub mod vm;
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 use bytecode::ByteCodeStream;
 use core::fmt;
@@ -14,6 +17,7 @@ pub enum Type {
     String,
     Integer,
     Boolean,
+    Float,
 }
 
 impl fmt::Display for Type {
@@ -23,6 +27,7 @@ impl fmt::Display for Type {
             Self::String => write!(f, "str"),
             Self::Integer => write!(f, "int"),
             Self::Boolean => write!(f, "bool"),
+            Self::Float => write!(f, "float"),
         }
     }
 }
@@ -34,11 +39,33 @@ pub struct FnPrototype {
     pub return_type: Type,
 }
 
+impl FnPrototype {
+    pub fn make(argtypes: Vec<Type>, return_type: Type) -> Self {
+        Self {
+            arity: argtypes.len() as u8,
+            argtypes,
+            return_type,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Object {
     Int(i32),
     String(String),
     Boolean(bool),
+    Float(f64),
+}
+
+impl Object {
+    pub fn type_(&self) -> Type {
+        match self {
+            Self::Int(_) => Type::Integer,
+            Self::String(_) => Type::String,
+            Self::Boolean(_) => Type::Boolean,
+            Self::Float(_) => Type::Float,
+        }
+    }
 }
 
 impl fmt::Display for Object {
@@ -47,6 +74,8 @@ impl fmt::Display for Object {
             Self::Int(x) => write!(f, "{}", x),
             Self::String(x) => write!(f, "{}", x),
             Self::Boolean(x) => write!(f, "{}", x),
+            // keeps the `.0` of whole floats so they don't read as ints
+            Self::Float(x) => write!(f, "{:?}", x),
         }
     }
 }
@@ -54,14 +83,67 @@ impl fmt::Display for Object {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Program {
     pub pool: Pool,
-    pub fns: HashMap<String, DefinedFn>,
+    /// Defined functions, indexed by `Invoke` and `TailInvoke`
+    pub fns: Vec<DefinedFn>,
+    /// Names of the native functions used, indexed by `InvokeNative` and linked by name when the
+    /// program is loaded
+    pub natives: Vec<String>,
+}
+
+impl Program {
+    /// Index of the defined function called `name`
+    pub fn fn_index(&self, name: &str) -> Option<usize> {
+        self.fns
+            .iter()
+            .position(|defined_fn| defined_fn.name == name)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DefinedFn {
+    pub name: String,
     pub arity: usize,
     pub code: ByteCodeStream,
     pub max_locals: usize,
+    pub lines: LineTable,
+}
+
+/// Maps bytecode offsets of a function back to source lines, each entry holds the first offset
+/// generated from a line
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LineTable {
+    entries: Vec<(usize, usize)>,
+}
+
+impl LineTable {
+    pub fn make() -> Self {
+        Self { entries: vec![] }
+    }
+
+    pub fn mark(&mut self, pc: usize, line: usize) {
+        match self.entries.last_mut() {
+            Some((_, last_line)) if *last_line == line => {}
+            Some((last_pc, last_line)) if *last_pc == pc => *last_line = line,
+            _ => self.entries.push((pc, line)),
+        }
+    }
+
+    /// First offsets of every run of instructions generated from `line`
+    pub fn offsets_of(&self, line: usize) -> Vec<usize> {
+        self.entries
+            .iter()
+            .filter(|(_, entry_line)| *entry_line == line)
+            .map(|(pc, _)| *pc)
+            .collect()
+    }
+
+    pub fn line_at(&self, pc: usize) -> Option<usize> {
+        self.entries
+            .iter()
+            .take_while(|(start, _)| *start <= pc)
+            .last()
+            .map(|(_, line)| *line)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1,10 +1,52 @@
-use std::collections::HashMap;
+use std::rc::Rc;
 
 use crate::{FnPrototype, Object, Type};
 
+/// Host function callable from Bug, arguments are given in declaration order
+pub type NativeFunction = Rc<dyn Fn(Vec<Object>) -> Option<Object>>;
+
+#[derive(Clone)]
 pub struct NativeFn {
+    pub name: String,
     pub prototype: FnPrototype,
-    pub function: fn(Vec<Object>) -> Option<Object>,
+    pub function: NativeFunction,
+}
+
+/// Registry of the native functions visible to Bug programs, the same registry is seen by the
+/// analyser to type check calls and by the virtual machine to link and execute them
+#[derive(Clone, Default)]
+pub struct Natives {
+    fns: Vec<NativeFn>,
+}
+
+impl Natives {
+    pub fn make() -> Self {
+        Self { fns: vec![] }
+    }
+
+    /// Registers `function` under `name`, replacing any native previously registered with it
+    pub fn register<F>(&mut self, name: &str, prototype: FnPrototype, function: F)
+    where
+        F: Fn(Vec<Object>) -> Option<Object> + 'static,
+    {
+        let native_fn = NativeFn {
+            name: name.to_string(),
+            prototype,
+            function: Rc::new(function),
+        };
+        match self.fns.iter_mut().find(|native_fn| native_fn.name == name) {
+            Some(registered) => *registered = native_fn,
+            None => self.fns.push(native_fn),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NativeFn> {
+        self.fns.iter().find(|native_fn| native_fn.name == name)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, NativeFn> {
+        self.fns.iter()
+    }
 }
 
 fn write_fn(args: Vec<Object>) -> Option<Object> {
@@ -14,17 +56,13 @@ fn write_fn(args: Vec<Object>) -> Option<Object> {
     None
 }
 
-pub fn list_native_fns() -> HashMap<String, NativeFn> {
+pub fn list_native_fns() -> Natives {
     let write_fn_prototype = FnPrototype {
         arity: 1,
         argtypes: vec![],
         return_type: Type::Void,
     };
-    let write_fn = NativeFn {
-        prototype: write_fn_prototype,
-        function: write_fn,
-    };
-    let mut fns: HashMap<String, NativeFn> = HashMap::new();
-    fns.insert("write".to_string(), write_fn);
+    let mut fns = Natives::make();
+    fns.register("write", write_fn_prototype, write_fn);
     fns
 }
@@ -1,5 +1,13 @@
 use std::fs::File;
 use std::io::{self, Read};
+use std::path::Path;
+
+pub fn read_file(path: &str) -> Result<String, io::Error> {
+    let mut file = File::open(path)?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents)?;
+    Ok(contents)
+}
 
 pub fn read_file_bytes(path: &str) -> Result<Vec<u8>, io::Error> {
     let mut file = File::open(path)?;
@@ -7,3 +15,23 @@ pub fn read_file_bytes(path: &str) -> Result<Vec<u8>, io::Error> {
     file.read_to_end(&mut contents)?;
     Ok(contents)
 }
+
+/// Where the binary of `input` goes when no output path is provided, `dir/main.bug` becomes
+/// `dir/main.bin`
+pub fn default_output(input: &str) -> String {
+    Path::new(input)
+        .with_extension("bin")
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::default_output;
+
+    #[test]
+    fn ensure_default_output_is_next_to_input() {
+        assert_eq!("examples/fib.bin", default_output("examples/fib.bug"));
+        assert_eq!("main.bin", default_output("main"));
+    }
+}
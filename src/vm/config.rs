@@ -0,0 +1,37 @@
+use crate::bytecode::Opcode;
+
+pub const DEFAULT_MAX_DEPTH: usize = 10_000;
+pub const DEFAULT_MAX_STACK: usize = 1_024;
+
+/// Settings applied to every program run by a `Vm`
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Fuel available to a run, `None` runs without bound
+    pub fuel: Option<u64>,
+    /// Fuel charged for executing an instruction
+    pub fuel_cost: fn(&Opcode) -> u64,
+    /// Maximum number of frames alive at once, `main`'s included
+    pub max_depth: usize,
+    /// Maximum number of values on the operand stack of a frame
+    pub max_stack: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            fuel: None,
+            fuel_cost: default_fuel_cost,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_stack: DEFAULT_MAX_STACK,
+        }
+    }
+}
+
+/// Calls cost more than other instructions as they set up a frame or leave the virtual machine
+pub fn default_fuel_cost(instr: &Opcode) -> u64 {
+    match instr {
+        Opcode::Invoke(_) | Opcode::TailInvoke(_) => 10,
+        Opcode::InvokeNative(_) => 20,
+        _ => 1,
+    }
+}
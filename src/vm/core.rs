@@ -0,0 +1,581 @@
+use crate::vm::config::Config;
+use crate::vm::error::{TraceEntry, VmError, VmErrorKind};
+use crate::vm::frame::Frame;
+use crate::vm::stack::Stack;
+
+use crate::bytecode::{Opcode, PushOperand};
+use crate::stdlib::{NativeFn, Natives};
+use crate::{Object, PoolEntry, Program, Type};
+
+/// Execution state of a program, driven one instruction at a time by `step`
+pub struct Runtime<'a> {
+    program: &'a Program,
+    natives: Vec<&'a NativeFn>,
+    config: Config,
+    fuel: Option<u64>,
+    framestack: Stack<Frame<'a>>,
+    current_frame: Frame<'a>,
+}
+
+impl<'a> Runtime<'a> {
+    /// Links the natives used by `program` and sets up the frame of `main`
+    pub fn make(
+        program: &'a Program,
+        native_fns: &'a Natives,
+        config: Config,
+    ) -> Result<Self, VmError> {
+        let main_index = match program.fn_index("main") {
+            Some(index) => index,
+            None => {
+                return Err(Self::setup_error(
+                    "main",
+                    VmErrorKind::UnknownFunction("main".to_string()),
+                ))
+            }
+        };
+        Self::resume(program, native_fns, config, main_index, vec![], vec![])
+    }
+
+    /// Sets up the frame of the function at `entry` already holding `stack` and `locals`, so a
+    /// program picks up the values left by a previous one like the lines of the REPL do
+    pub fn resume(
+        program: &'a Program,
+        native_fns: &'a Natives,
+        config: Config,
+        entry: usize,
+        stack: Vec<Object>,
+        locals: Vec<Object>,
+    ) -> Result<Self, VmError> {
+        let entry_fn = match program.fns.get(entry) {
+            Some(entry_fn) => entry_fn,
+            None => {
+                return Err(Self::setup_error(
+                    "main",
+                    VmErrorKind::FunctionOutOfRange(entry),
+                ))
+            }
+        };
+        let mut natives: Vec<&NativeFn> = vec![];
+        for name in &program.natives {
+            match native_fns.get(name) {
+                Some(native_fn) => natives.push(native_fn),
+                None => {
+                    return Err(Self::setup_error(
+                        &entry_fn.name,
+                        VmErrorKind::UnknownFunction(name.clone()),
+                    ))
+                }
+            }
+        }
+
+        let mut frame = Frame::make(entry, entry_fn);
+        for (index, local) in locals.into_iter().enumerate() {
+            frame
+                .locals
+                .store_at(index, local)
+                .map_err(|kind| Self::setup_error(&entry_fn.name, kind))?;
+        }
+        for object in stack {
+            frame.stack.push(object);
+        }
+
+        Ok(Self {
+            program,
+            natives,
+            fuel: config.fuel,
+            config,
+            framestack: Stack::make(),
+            current_frame: frame,
+        })
+    }
+
+    fn setup_error(function: &str, kind: VmErrorKind) -> VmError {
+        VmError {
+            kind,
+            function: function.to_string(),
+            pc: 0,
+            trace: vec![],
+        }
+    }
+
+    pub fn run(&mut self) -> Result<(), VmError> {
+        while self.step()? {}
+        Ok(())
+    }
+
+    /// Executes a single instruction, returns false once `main` returns
+    pub fn step(&mut self) -> Result<bool, VmError> {
+        self.execute().map_err(|kind| self.error(kind))
+    }
+
+    /// Fuel left, `None` when running without bound
+    pub fn fuel(&self) -> Option<u64> {
+        self.fuel
+    }
+
+    /// Name of the function being executed
+    pub fn function(&self) -> &'a str {
+        &self.program.fns[self.current_frame.fn_index].name
+    }
+
+    /// Offset of the next instruction to execute
+    pub fn pc(&self) -> usize {
+        self.current_frame.pc
+    }
+
+    pub fn next_instr(&self) -> Option<&'a Opcode> {
+        self.current_frame.peek_instr()
+    }
+
+    /// Operand stack of the function being executed, top last
+    pub fn stack(&self) -> &[Object] {
+        &self.current_frame.stack.inner
+    }
+
+    pub fn locals(&self) -> &[Object] {
+        self.current_frame.locals.as_slice()
+    }
+
+    /// Source line of the next instruction, if the compiler recorded one
+    pub fn line(&self) -> Option<usize> {
+        self.program.fns[self.current_frame.fn_index]
+            .lines
+            .line_at(self.current_frame.pc)
+    }
+
+    /// Number of frames alive, `main`'s included
+    pub fn depth(&self) -> usize {
+        self.framestack.inner.len() + 1
+    }
+
+    /// Frames alive innermost first, the current one positioned at its next instruction
+    pub fn backtrace(&self) -> Vec<TraceEntry> {
+        self.trace(self.current_frame.pc)
+    }
+
+    fn execute(&mut self) -> Result<bool, VmErrorKind> {
+        let program = self.program;
+        let natives = &self.natives;
+        let framestack = &mut self.framestack;
+        let current_frame = &mut self.current_frame;
+
+        let instr = current_frame.fetch_next_instr()?;
+        if let Some(fuel) = self.fuel.as_mut() {
+            let cost = (self.config.fuel_cost)(&instr);
+            if *fuel < cost {
+                return Err(VmErrorKind::OutOfFuel);
+            }
+            *fuel -= cost;
+        }
+        match instr {
+            Opcode::Nop => {}
+            Opcode::IAdd => Self::iarith(current_frame, i32::checked_add)?,
+            Opcode::ISub => Self::iarith(current_frame, i32::checked_sub)?,
+            Opcode::IMul => Self::iarith(current_frame, i32::checked_mul)?,
+            Opcode::IDiv => Self::idiv(current_frame, i32::checked_div)?,
+            Opcode::IRem => Self::idiv(current_frame, i32::checked_rem)?,
+            Opcode::LLoad(index) => {
+                let local = current_frame.locals.get_by_index(index)?;
+                current_frame.stack.push(local);
+            }
+            Opcode::LStore(index) => {
+                let val = current_frame.pop()?;
+                current_frame.locals.store_at(index, val)?;
+            }
+            Opcode::Invoke(index) => {
+                if framestack.inner.len() + 2 > self.config.max_depth {
+                    return Err(VmErrorKind::StackOverflow);
+                }
+                let callee_frame = Self::make_callee_frame(program, index, current_frame)?;
+                let caller_frame = std::mem::replace(current_frame, callee_frame);
+                framestack.push(caller_frame);
+            }
+            Opcode::TailInvoke(index) => {
+                *current_frame = Self::make_callee_frame(program, index, current_frame)?;
+            }
+            Opcode::InvokeNative(index) => {
+                let native_fn = natives
+                    .get(index)
+                    .ok_or(VmErrorKind::FunctionOutOfRange(index))?;
+                let mut args: Vec<Object> = vec![];
+                for _ in 0..native_fn.prototype.arity {
+                    args.push(current_frame.pop()?);
+                }
+                args.reverse();
+                // the verifier trusts the prototype, a native breaking it would corrupt the stack
+                let return_val = (native_fn.function)(args);
+                let found = return_val.as_ref().map_or(Type::Void, Object::type_);
+                if found != native_fn.prototype.return_type {
+                    return Err(VmErrorKind::NativeTypeMismatch {
+                        name: native_fn.name.clone(),
+                        expected: native_fn.prototype.return_type.clone(),
+                        found,
+                    });
+                }
+                if let Some(return_val) = return_val {
+                    current_frame.stack.push(return_val);
+                }
+            }
+            Opcode::ReturnTop => {
+                let val = current_frame.pop()?;
+                let mut parent_frame = framestack.pop().ok_or(VmErrorKind::StackUnderflow)?;
+                parent_frame.stack.push(val);
+                *current_frame = parent_frame;
+            }
+            Opcode::Return => match framestack.pop() {
+                Some(parent_frame) => *current_frame = parent_frame,
+                None => return Ok(false),
+            },
+            Opcode::ICmpGT => Self::icmp(current_frame, |lhs, rhs| lhs > rhs)?,
+            Opcode::ICmpGE => Self::icmp(current_frame, |lhs, rhs| lhs >= rhs)?,
+            Opcode::ICmpLT => Self::icmp(current_frame, |lhs, rhs| lhs < rhs)?,
+            Opcode::ICmpLE => Self::icmp(current_frame, |lhs, rhs| lhs <= rhs)?,
+            Opcode::ICmpEQ => Self::icmp(current_frame, |lhs, rhs| lhs == rhs)?,
+            Opcode::ICmpNE => Self::icmp(current_frame, |lhs, rhs| lhs != rhs)?,
+            Opcode::FAdd => Self::farith(current_frame, |lhs, rhs| lhs + rhs)?,
+            Opcode::FSub => Self::farith(current_frame, |lhs, rhs| lhs - rhs)?,
+            Opcode::FMul => Self::farith(current_frame, |lhs, rhs| lhs * rhs)?,
+            Opcode::FDiv => Self::farith(current_frame, |lhs, rhs| lhs / rhs)?,
+            Opcode::FCmpGT => Self::fcmp(current_frame, |lhs, rhs| lhs > rhs)?,
+            Opcode::FCmpGE => Self::fcmp(current_frame, |lhs, rhs| lhs >= rhs)?,
+            Opcode::FCmpLT => Self::fcmp(current_frame, |lhs, rhs| lhs < rhs)?,
+            Opcode::FCmpLE => Self::fcmp(current_frame, |lhs, rhs| lhs <= rhs)?,
+            Opcode::FCmpEQ => Self::fcmp(current_frame, |lhs, rhs| lhs == rhs)?,
+            Opcode::FCmpNE => Self::fcmp(current_frame, |lhs, rhs| lhs != rhs)?,
+            Opcode::I2F => {
+                let val = Self::ipop(current_frame)?;
+                current_frame.stack.push(Object::Float(val as f64));
+            }
+            Opcode::F2I => {
+                let val = Self::fpop(current_frame)?.trunc();
+                if val.is_nan() || val < i32::MIN as f64 || val > i32::MAX as f64 {
+                    return Err(VmErrorKind::IntegerOverflow);
+                }
+                current_frame.stack.push(Object::Int(val as i32));
+            }
+            Opcode::CmpEQ => Self::cmp(current_frame, |lhs, rhs| lhs == rhs)?,
+            Opcode::CmpNE => Self::cmp(current_frame, |lhs, rhs| lhs != rhs)?,
+            Opcode::And => Self::logic(current_frame, |lhs, rhs| lhs && rhs)?,
+            Opcode::Or => Self::logic(current_frame, |lhs, rhs| lhs || rhs)?,
+            Opcode::Dup => {
+                let top = current_frame.pop()?;
+                current_frame.stack.push(top.clone());
+                current_frame.stack.push(top);
+            }
+            Opcode::Drop => {
+                current_frame.pop()?;
+            }
+            Opcode::Swap => {
+                let b = current_frame.pop()?;
+                let a = current_frame.pop()?;
+                current_frame.stack.push(b);
+                current_frame.stack.push(a);
+            }
+            Opcode::Over => {
+                let b = current_frame.pop()?;
+                let a = current_frame.pop()?;
+                current_frame.stack.push(a.clone());
+                current_frame.stack.push(b);
+                current_frame.stack.push(a);
+            }
+            Opcode::Rot => {
+                let c = current_frame.pop()?;
+                let b = current_frame.pop()?;
+                let a = current_frame.pop()?;
+                current_frame.stack.push(b);
+                current_frame.stack.push(c);
+                current_frame.stack.push(a);
+            }
+            Opcode::Not => {
+                let val = Self::bpop(current_frame)?;
+                current_frame.stack.push(Object::Boolean(!val));
+            }
+            Opcode::JumpIfFalse(offset) => {
+                if !Self::bpop(current_frame)? {
+                    current_frame.pc = offset;
+                }
+            }
+            Opcode::Push(val) => match val {
+                PushOperand::Integer(x) => {
+                    current_frame.stack.push(Object::Int(x));
+                }
+                PushOperand::Boolean(x) => {
+                    current_frame.stack.push(Object::Boolean(x));
+                }
+            },
+            Opcode::Ldc(index) => match program.pool.entries.get(index) {
+                Some(PoolEntry::Object(object)) => current_frame.stack.push(object.clone()),
+                None => return Err(VmErrorKind::PoolOutOfRange(index)),
+            },
+            Opcode::Jump(offset) => current_frame.pc = offset,
+        }
+        if current_frame.stack.inner.len() > self.config.max_stack {
+            return Err(VmErrorKind::StackOverflow);
+        }
+        Ok(true)
+    }
+
+    /// Makes the frame of the defined function at `index`, moving the arguments from the caller's
+    /// stack to the callee's locals
+    fn make_callee_frame(
+        program: &'a Program,
+        index: usize,
+        current_frame: &mut Frame<'a>,
+    ) -> Result<Frame<'a>, VmErrorKind> {
+        let callee = program
+            .fns
+            .get(index)
+            .ok_or(VmErrorKind::FunctionOutOfRange(index))?;
+        let mut callee_frame = Frame::make(index, callee);
+        for index in 0..callee.arity {
+            callee_frame
+                .locals
+                .store_at(callee.arity - index - 1, current_frame.pop()?)?;
+        }
+        Ok(callee_frame)
+    }
+
+    /// Walks the frames innermost first, callers are reported at the offset of their call
+    fn trace(&self, innermost_pc: usize) -> Vec<TraceEntry> {
+        std::iter::once((&self.current_frame, innermost_pc))
+            .chain(
+                self.framestack
+                    .inner
+                    .iter()
+                    .rev()
+                    .map(|frame| (frame, frame.current_pc())),
+            )
+            .map(|(frame, pc)| {
+                let defined_fn = &self.program.fns[frame.fn_index];
+                TraceEntry {
+                    function: defined_fn.name.clone(),
+                    pc,
+                    line: defined_fn.lines.line_at(pc),
+                }
+            })
+            .collect()
+    }
+
+    fn error(&self, kind: VmErrorKind) -> VmError {
+        let trace = self.trace(self.current_frame.current_pc());
+        VmError {
+            kind,
+            function: trace[0].function.clone(),
+            pc: self.current_frame.current_pc(),
+            trace,
+        }
+    }
+
+    fn ipop(current_frame: &mut Frame) -> Result<i32, VmErrorKind> {
+        match current_frame.pop()? {
+            Object::Int(x) => Ok(x),
+            unexpected => Err(VmErrorKind::TypeMismatch {
+                expected: Type::Integer,
+                found: unexpected.type_(),
+            }),
+        }
+    }
+
+    fn ipop_two(current_frame: &mut Frame) -> Result<(i32, i32), VmErrorKind> {
+        let snd = Self::ipop(current_frame)?;
+        let fst = Self::ipop(current_frame)?;
+        Ok((fst, snd))
+    }
+
+    fn iarith(
+        current_frame: &mut Frame,
+        operation: fn(i32, i32) -> Option<i32>,
+    ) -> Result<(), VmErrorKind> {
+        let (lhs, rhs) = Self::ipop_two(current_frame)?;
+        let result = operation(lhs, rhs).ok_or(VmErrorKind::IntegerOverflow)?;
+        current_frame.stack.push(Object::Int(result));
+        Ok(())
+    }
+
+    fn idiv(
+        current_frame: &mut Frame,
+        operation: fn(i32, i32) -> Option<i32>,
+    ) -> Result<(), VmErrorKind> {
+        let (lhs, rhs) = Self::ipop_two(current_frame)?;
+        if rhs == 0 {
+            return Err(VmErrorKind::DivisionByZero);
+        }
+        let result = operation(lhs, rhs).ok_or(VmErrorKind::IntegerOverflow)?;
+        current_frame.stack.push(Object::Int(result));
+        Ok(())
+    }
+
+    fn icmp(current_frame: &mut Frame, predicate: fn(i32, i32) -> bool) -> Result<(), VmErrorKind> {
+        let (lhs, rhs) = Self::ipop_two(current_frame)?;
+        current_frame
+            .stack
+            .push(Object::Boolean(predicate(lhs, rhs)));
+        Ok(())
+    }
+
+    fn fpop(current_frame: &mut Frame) -> Result<f64, VmErrorKind> {
+        match current_frame.pop()? {
+            Object::Float(x) => Ok(x),
+            unexpected => Err(VmErrorKind::TypeMismatch {
+                expected: Type::Float,
+                found: unexpected.type_(),
+            }),
+        }
+    }
+
+    fn fpop_two(current_frame: &mut Frame) -> Result<(f64, f64), VmErrorKind> {
+        let snd = Self::fpop(current_frame)?;
+        let fst = Self::fpop(current_frame)?;
+        Ok((fst, snd))
+    }
+
+    fn farith(
+        current_frame: &mut Frame,
+        operation: fn(f64, f64) -> f64,
+    ) -> Result<(), VmErrorKind> {
+        let (lhs, rhs) = Self::fpop_two(current_frame)?;
+        current_frame.stack.push(Object::Float(operation(lhs, rhs)));
+        Ok(())
+    }
+
+    fn fcmp(current_frame: &mut Frame, predicate: fn(f64, f64) -> bool) -> Result<(), VmErrorKind> {
+        let (lhs, rhs) = Self::fpop_two(current_frame)?;
+        current_frame
+            .stack
+            .push(Object::Boolean(predicate(lhs, rhs)));
+        Ok(())
+    }
+
+    fn cmp(
+        current_frame: &mut Frame,
+        predicate: fn(&Object, &Object) -> bool,
+    ) -> Result<(), VmErrorKind> {
+        let rhs = current_frame.pop()?;
+        let lhs = current_frame.pop()?;
+        current_frame
+            .stack
+            .push(Object::Boolean(predicate(&lhs, &rhs)));
+        Ok(())
+    }
+
+    fn bpop(current_frame: &mut Frame) -> Result<bool, VmErrorKind> {
+        match current_frame.pop()? {
+            Object::Boolean(x) => Ok(x),
+            unexpected => Err(VmErrorKind::TypeMismatch {
+                expected: Type::Boolean,
+                found: unexpected.type_(),
+            }),
+        }
+    }
+
+    fn logic(
+        current_frame: &mut Frame,
+        operation: fn(bool, bool) -> bool,
+    ) -> Result<(), VmErrorKind> {
+        let rhs = Self::bpop(current_frame)?;
+        let lhs = Self::bpop(current_frame)?;
+        current_frame
+            .stack
+            .push(Object::Boolean(operation(lhs, rhs)));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stdlib::list_native_fns;
+    use crate::vm::testing::program;
+
+    fn run(program: &Program) -> Result<(), VmError> {
+        let natives = list_native_fns();
+        Runtime::make(program, &natives, Config::default())?.run()
+    }
+
+    #[test]
+    fn ensure_division_by_zero_is_reported_with_a_trace() {
+        let program = program(vec![
+            (
+                "main",
+                0,
+                vec![
+                    Opcode::Push(PushOperand::Integer(1)),
+                    Opcode::Invoke(1),
+                    Opcode::Return,
+                ],
+            ),
+            (
+                "half",
+                1,
+                vec![
+                    Opcode::LLoad(0),
+                    Opcode::Push(PushOperand::Integer(0)),
+                    Opcode::IDiv,
+                    Opcode::ReturnTop,
+                ],
+            ),
+        ]);
+
+        let err = run(&program).unwrap_err();
+        assert_eq!(VmErrorKind::DivisionByZero, err.kind);
+        assert_eq!("half", err.function);
+        assert_eq!(2, err.pc);
+        let trace = err
+            .trace
+            .iter()
+            .map(|entry| (entry.function.as_str(), entry.pc, entry.line))
+            .collect::<Vec<(&str, usize, Option<usize>)>>();
+        assert_eq!(vec![("half", 2, None), ("main", 1, None)], trace);
+    }
+
+    #[test]
+    fn ensure_bad_bytecode_is_an_error_not_a_panic() {
+        let err = run(&program(vec![("main", 0, vec![Opcode::Drop])])).unwrap_err();
+        assert_eq!(VmErrorKind::StackUnderflow, err.kind);
+
+        let err = run(&program(vec![(
+            "main",
+            0,
+            vec![
+                Opcode::Push(PushOperand::Integer(1)),
+                Opcode::JumpIfFalse(0),
+            ],
+        )]))
+        .unwrap_err();
+        assert_eq!(
+            VmErrorKind::TypeMismatch {
+                expected: Type::Boolean,
+                found: Type::Integer
+            },
+            err.kind
+        );
+
+        let err = run(&program(vec![("main", 0, vec![Opcode::Invoke(7)])])).unwrap_err();
+        assert_eq!(VmErrorKind::FunctionOutOfRange(7), err.kind);
+
+        let err = run(&program(vec![("main", 0, vec![Opcode::LLoad(3)])])).unwrap_err();
+        assert_eq!(VmErrorKind::LocalOutOfRange(3), err.kind);
+    }
+
+    #[test]
+    fn ensure_floats_convert_to_ints_within_range() {
+        let mut program = program(vec![(
+            "main",
+            0,
+            vec![
+                Opcode::Push(PushOperand::Integer(7)),
+                Opcode::I2F,
+                Opcode::Ldc(0),
+                Opcode::FDiv,
+                Opcode::F2I,
+                Opcode::Return,
+            ],
+        )]);
+        program.pool.append(PoolEntry::Object(Object::Float(2.0)));
+        assert!(run(&program).is_ok());
+
+        program.pool.entries[0] = PoolEntry::Object(Object::Float(0.0));
+        let err = run(&program).unwrap_err();
+        assert_eq!(VmErrorKind::IntegerOverflow, err.kind);
+    }
+}
@@ -0,0 +1,103 @@
+use core::fmt;
+
+use crate::Type;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VmErrorKind {
+    DivisionByZero,
+    IntegerOverflow,
+    StackUnderflow,
+    StackOverflow,
+    TypeMismatch {
+        expected: Type,
+        found: Type,
+    },
+    /// A host native returned something else than the return type of its prototype
+    NativeTypeMismatch {
+        name: String,
+        expected: Type,
+        found: Type,
+    },
+    UnknownFunction(String),
+    FunctionOutOfRange(usize),
+    LocalOutOfRange(usize),
+    PoolOutOfRange(usize),
+    CodeOutOfRange,
+    OutOfFuel,
+}
+
+impl fmt::Display for VmErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DivisionByZero => write!(f, "Division by zero"),
+            Self::IntegerOverflow => write!(f, "Integer overflow"),
+            Self::StackUnderflow => write!(f, "Stack underflow"),
+            Self::StackOverflow => write!(f, "Stack overflow"),
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "Type mismatch, expected {} but got {}", expected, found)
+            }
+            Self::NativeTypeMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Type mismatch, native '{}' returned {} but declares {}",
+                name, found, expected
+            ),
+            Self::UnknownFunction(name) => write!(f, "Unknown function '{}'", name),
+            Self::FunctionOutOfRange(index) => write!(f, "Function index {} out of range", index),
+            Self::LocalOutOfRange(index) => write!(f, "Local index {} out of range", index),
+            Self::PoolOutOfRange(index) => write!(f, "Pool index {} out of range", index),
+            Self::CodeOutOfRange => write!(f, "Execution fell off the end of code"),
+            Self::OutOfFuel => write!(f, "Out of fuel"),
+        }
+    }
+}
+
+/// A function being executed when the error happened
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceEntry {
+    pub function: String,
+    pub pc: usize,
+    /// Source line of the instruction, if the compiler recorded one
+    pub line: Option<usize>,
+}
+
+impl fmt::Display for TraceEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{} (pc {}, line {})", self.function, self.pc, line),
+            None => write!(f, "{} (pc {})", self.function, self.pc),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VmError {
+    pub kind: VmErrorKind,
+    pub function: String,
+    pub pc: usize,
+    /// Innermost call first, the failing function included
+    pub trace: Vec<TraceEntry>,
+}
+
+/// Frames printed at each end of a long trace, like the one of a stack overflow
+const TRACE_EDGE: usize = 10;
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        for (index, entry) in self.trace.iter().enumerate() {
+            let hidden = self.trace.len().saturating_sub(TRACE_EDGE * 2);
+            if hidden > 0 && index >= TRACE_EDGE && index < TRACE_EDGE + hidden {
+                if index == TRACE_EDGE {
+                    write!(f, "\n    ... {} more frames", hidden)?;
+                }
+                continue;
+            }
+            write!(f, "\n    at {}", entry)?;
+        }
+        Ok(())
+    }
+}
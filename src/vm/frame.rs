@@ -0,0 +1,86 @@
+use crate::bytecode::{ByteCodeStream, Opcode};
+use crate::vm::error::VmErrorKind;
+use crate::vm::stack::Stack;
+use crate::DefinedFn;
+use crate::Object;
+
+#[derive(Debug, Clone)]
+pub struct Locals {
+    inner: Vec<Object>,
+}
+
+impl Locals {
+    pub fn make(max_locals: usize) -> Self {
+        let mut inner: Vec<Object> = Vec::with_capacity(max_locals);
+        for _ in 0..max_locals {
+            inner.push(Object::Int(0)) // init locals with zeros
+        }
+        Self { inner }
+    }
+
+    pub fn get_by_index(&self, index: usize) -> Result<Object, VmErrorKind> {
+        match self.inner.get(index) {
+            Some(object) => Ok(object.clone()),
+            None => Err(VmErrorKind::LocalOutOfRange(index)),
+        }
+    }
+
+    pub fn as_slice(&self) -> &[Object] {
+        &self.inner
+    }
+
+    pub fn store_at(&mut self, index: usize, o: Object) -> Result<(), VmErrorKind> {
+        match self.inner.get_mut(index) {
+            Some(local) => {
+                *local = o;
+                Ok(())
+            }
+            None => Err(VmErrorKind::LocalOutOfRange(index)),
+        }
+    }
+}
+
+/// The code is borrowed from the program, so making a frame for a call never copies bytecode
+#[derive(Debug, Clone)]
+pub struct Frame<'a> {
+    pub fn_index: usize,
+    pub pc: usize,
+    code: &'a ByteCodeStream,
+    pub locals: Locals,
+    pub stack: Stack<Object>,
+}
+
+impl<'a> Frame<'a> {
+    pub fn make(fn_index: usize, defined_fn: &'a DefinedFn) -> Self {
+        Self {
+            fn_index,
+            pc: 0,
+            code: &defined_fn.code,
+            stack: Stack::make(),
+            locals: Locals::make(defined_fn.max_locals),
+        }
+    }
+
+    pub fn fetch_next_instr(&mut self) -> Result<Opcode, VmErrorKind> {
+        let instr = self
+            .code
+            .get_at(self.pc)
+            .ok_or(VmErrorKind::CodeOutOfRange)?;
+        self.pc += 1;
+        Ok(instr.clone())
+    }
+
+    /// Instruction executed by the next `fetch_next_instr`
+    pub fn peek_instr(&self) -> Option<&'a Opcode> {
+        self.code.get_at(self.pc)
+    }
+
+    pub fn pop(&mut self) -> Result<Object, VmErrorKind> {
+        self.stack.pop().ok_or(VmErrorKind::StackUnderflow)
+    }
+
+    /// Offset of the instruction being executed (or of the call for a caller frame)
+    pub fn current_pc(&self) -> usize {
+        self.pc.saturating_sub(1)
+    }
+}
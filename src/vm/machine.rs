@@ -0,0 +1,220 @@
+use crate::bytecode::Opcode;
+use crate::compiler::{self, diagnostics::Diagnostic};
+use crate::stdlib::{list_native_fns, Natives};
+use crate::vm::config::Config;
+use crate::vm::core::Runtime;
+use crate::vm::error::VmError;
+use crate::vm::verifier::{verify, VerifyError};
+use crate::{FnPrototype, Object, Program};
+
+/// Entry point for embedding Bug, programs are compiled and run against the natives registered
+/// on the builder
+pub struct Vm {
+    natives: Natives,
+    config: Config,
+}
+
+impl Vm {
+    pub fn builder() -> VmBuilder {
+        VmBuilder {
+            natives: list_native_fns(),
+            config: Config::default(),
+        }
+    }
+
+    pub fn natives(&self) -> &Natives {
+        &self.natives
+    }
+
+    pub fn compile(&self, source: &str) -> Result<Program, Vec<Diagnostic>> {
+        compiler::compile_with(source, &self.natives)
+    }
+
+    pub fn verify(&self, program: &Program) -> Result<(), VerifyError> {
+        verify(program, &self.natives)
+    }
+
+    pub fn run(&self, program: &Program) -> Result<(), VmError> {
+        self.runtime(program)?.run()
+    }
+
+    /// Sets up `program` to be executed one instruction at a time with `Runtime::step`
+    pub fn runtime<'a>(&'a self, program: &'a Program) -> Result<Runtime<'a>, VmError> {
+        Runtime::make(program, &self.natives, self.config.clone())
+    }
+
+    /// Like `runtime` but starting at the function at `entry` with `stack` and `locals` in place
+    pub fn resume<'a>(
+        &'a self,
+        program: &'a Program,
+        entry: usize,
+        stack: Vec<Object>,
+        locals: Vec<Object>,
+    ) -> Result<Runtime<'a>, VmError> {
+        Runtime::resume(
+            program,
+            &self.natives,
+            self.config.clone(),
+            entry,
+            stack,
+            locals,
+        )
+    }
+}
+
+pub struct VmBuilder {
+    natives: Natives,
+    config: Config,
+}
+
+impl VmBuilder {
+    /// Registers a host function callable from Bug as `.name`, the closure may capture state
+    pub fn native<F>(mut self, name: &str, prototype: FnPrototype, function: F) -> Self
+    where
+        F: Fn(Vec<Object>) -> Option<Object> + 'static,
+    {
+        self.natives.register(name, prototype, function);
+        self
+    }
+
+    /// Bounds every run to `fuel`, each instruction consumes some and the run fails once it's
+    /// exhausted
+    pub fn fuel(mut self, fuel: u64) -> Self {
+        self.config.fuel = Some(fuel);
+        self
+    }
+
+    /// Replaces the fuel charged per instruction, see `default_fuel_cost`
+    pub fn fuel_cost(mut self, fuel_cost: fn(&Opcode) -> u64) -> Self {
+        self.config.fuel_cost = fuel_cost;
+        self
+    }
+
+    /// Limits how deep calls can nest, see `DEFAULT_MAX_DEPTH`
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.config.max_depth = max_depth;
+        self
+    }
+
+    /// Limits how many values a function can hold on its operand stack, see `DEFAULT_MAX_STACK`
+    pub fn max_stack(mut self, max_stack: usize) -> Self {
+        self.config.max_stack = max_stack;
+        self
+    }
+
+    pub fn build(self) -> Vm {
+        Vm {
+            natives: self.natives,
+            config: self.config,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::vm::VmErrorKind;
+    use crate::Type;
+
+    #[test]
+    fn ensure_host_natives_are_typed_and_called_with_their_state() {
+        let calls = Rc::new(RefCell::new(vec![]));
+        let recorded = calls.clone();
+        let vm = Vm::builder()
+            .native(
+                "sub",
+                FnPrototype::make(vec![Type::Integer, Type::Integer], Type::Integer),
+                move |args| match (&args[0], &args[1]) {
+                    (Object::Int(lhs), Object::Int(rhs)) => {
+                        recorded.borrow_mut().push(args.clone());
+                        Some(Object::Int(lhs - rhs))
+                    }
+                    _ => None,
+                },
+            )
+            .build();
+
+        let program = vm.compile("f main -> 10 3 .sub 1 .sub .write;").unwrap();
+        assert_eq!(Ok(()), vm.verify(&program));
+        assert_eq!(Ok(()), vm.run(&program));
+        assert_eq!(
+            vec![
+                vec![Object::Int(10), Object::Int(3)],
+                vec![Object::Int(7), Object::Int(1)]
+            ],
+            *calls.borrow()
+        );
+
+        assert!(vm.compile("f main -> true 3 .sub .write;").is_err());
+    }
+
+    #[test]
+    fn ensure_natives_breaking_their_prototype_are_caught() {
+        let vm = Vm::builder()
+            .native("answer", FnPrototype::make(vec![], Type::Integer), |_| None)
+            .native("label", FnPrototype::make(vec![], Type::Integer), |_| {
+                Some(Object::String("42".to_string()))
+            })
+            .build();
+
+        let program = vm.compile("f main -> .answer .write;").unwrap();
+        let err = vm.run(&program).unwrap_err();
+        assert_eq!(
+            VmErrorKind::NativeTypeMismatch {
+                name: "answer".to_string(),
+                expected: Type::Integer,
+                found: Type::Void
+            },
+            err.kind
+        );
+        assert_eq!(0, err.pc);
+
+        let program = vm.compile("f main -> .label .write;").unwrap();
+        let err = vm.run(&program).unwrap_err();
+        assert_eq!(
+            "Type mismatch, native 'label' returned str but declares int",
+            err.kind.to_string()
+        );
+    }
+
+    #[test]
+    fn ensure_runs_stop_once_out_of_fuel() {
+        let source = "f spin -> .spin; f main -> .spin;";
+
+        let vm = Vm::builder().fuel(1000).build();
+        let program = vm.compile(source).unwrap();
+        let err = vm.run(&program).unwrap_err();
+        assert_eq!(VmErrorKind::OutOfFuel, err.kind);
+        assert_eq!("spin", err.function);
+
+        let vm = Vm::builder().fuel(1).fuel_cost(|_| 0).build();
+        let program = vm.compile("f main -> 1 2 + .write;").unwrap();
+        assert_eq!(Ok(()), vm.run(&program));
+
+        let vm = Vm::builder().fuel(2).build();
+        let err = vm.run(&program).unwrap_err();
+        assert_eq!(VmErrorKind::OutOfFuel, err.kind);
+        assert_eq!(2, err.pc);
+    }
+
+    #[test]
+    fn ensure_deep_recursion_overflows_cleanly() {
+        let source = "f deep(int n) int -> n 1 + .deep 1 + return; f main -> 0 .deep .write;";
+        let vm = Vm::builder().max_depth(50).build();
+        let program = vm.compile(source).unwrap();
+        let err = vm.run(&program).unwrap_err();
+        assert_eq!(VmErrorKind::StackOverflow, err.kind);
+        assert_eq!(50, err.trace.len());
+
+        let vm = Vm::builder().max_stack(3).build();
+        let program = vm.compile("f main -> 1 2 3 + + .write;").unwrap();
+        assert_eq!(Ok(()), vm.run(&program));
+        let program = vm.compile("f main -> 1 2 3 4 + + + .write;").unwrap();
+        let err = vm.run(&program).unwrap_err();
+        assert_eq!(VmErrorKind::StackOverflow, err.kind);
+        assert_eq!(3, err.pc);
+    }
+}
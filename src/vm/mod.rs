@@ -0,0 +1,15 @@
+mod config;
+mod core;
+mod error;
+mod frame;
+mod machine;
+mod stack;
+pub mod verifier;
+
+#[cfg(test)]
+pub(crate) mod testing;
+
+pub use self::config::{default_fuel_cost, Config, DEFAULT_MAX_DEPTH, DEFAULT_MAX_STACK};
+pub use self::core::Runtime;
+pub use self::error::{TraceEntry, VmError, VmErrorKind};
+pub use self::machine::{Vm, VmBuilder};
@@ -15,8 +15,4 @@ impl<T: Clone> Stack<T> {
     pub fn pop(&mut self) -> Option<T> {
         self.inner.pop()
     }
-
-    pub fn is_empty(&self) -> bool {
-        self.inner.is_empty()
-    }
 }
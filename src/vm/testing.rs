@@ -0,0 +1,21 @@
+use crate::bytecode::{ByteCodeStream, Opcode};
+use crate::{DefinedFn, LineTable, Pool, Program};
+
+/// Program made of `fns`, each given as name, arity and code, which may call the `write` native
+pub fn program(fns: Vec<(&str, usize, Vec<Opcode>)>) -> Program {
+    let fns = fns
+        .into_iter()
+        .map(|(name, arity, code)| DefinedFn {
+            name: name.to_string(),
+            arity,
+            code: ByteCodeStream::from(code),
+            max_locals: arity,
+            lines: LineTable::make(),
+        })
+        .collect();
+    Program {
+        pool: Pool::make(),
+        fns,
+        natives: vec!["write".to_string()],
+    }
+}
@@ -0,0 +1,397 @@
+use crate::bytecode::Opcode;
+use crate::stdlib::Natives;
+use crate::{DefinedFn, Program, Type};
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyError {
+    pub function: Option<String>,
+    pub pc: Option<usize>,
+    pub msg: String,
+}
+
+impl VerifyError {
+    fn make(function: Option<&str>, pc: Option<usize>, msg: String) -> Self {
+        Self {
+            function: function.map(|name| name.to_string()),
+            pc,
+            msg,
+        }
+    }
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.function, self.pc) {
+            (Some(function), Some(pc)) => {
+                write!(f, "{} (in '{}' at pc {})", self.msg, function, pc)
+            }
+            (Some(function), None) => write!(f, "{} (in '{}')", self.msg, function),
+            _ => write!(f, "{}", self.msg),
+        }
+    }
+}
+
+/// Statically checks a program before it gets executed, so that a well formed program can't make
+/// the virtual machine jump or index out of range, call unknown functions or underflow the stack
+pub fn verify(program: &Program, natives: &Natives) -> Result<(), VerifyError> {
+    let verifier = Verifier::make(program, natives)?;
+
+    let main_index = program
+        .fn_index("main")
+        .ok_or_else(|| VerifyError::make(None, None, "Missing 'main' function".to_string()))?;
+    let main_error = |msg: &str| Err(VerifyError::make(Some("main"), None, msg.to_string()));
+    if verifier.fns[main_index].arity != 0 {
+        return main_error("Function 'main' must not take arguments");
+    }
+    if verifier.fns[main_index].returns {
+        return main_error("Function 'main' must not return a value");
+    }
+
+    for (index, defined_fn) in program.fns.iter().enumerate() {
+        verifier.verify_fn(index, defined_fn)?;
+    }
+    Ok(())
+}
+
+/// How many values a callable pops and whether it pushes a result
+struct Signature {
+    arity: usize,
+    returns: bool,
+}
+
+struct Verifier<'a> {
+    program: &'a Program,
+    fns: Vec<Signature>,
+    natives: Vec<Signature>,
+}
+
+impl<'a> Verifier<'a> {
+    fn make(program: &'a Program, native_fns: &Natives) -> Result<Self, VerifyError> {
+        let mut natives = vec![];
+        for name in &program.natives {
+            match native_fns.get(name) {
+                Some(native_fn) => natives.push(Signature {
+                    arity: native_fn.prototype.arity as usize,
+                    returns: native_fn.prototype.return_type != Type::Void,
+                }),
+                None => {
+                    return Err(VerifyError::make(
+                        None,
+                        None,
+                        format!("Unknown native function '{}'", name),
+                    ))
+                }
+            }
+        }
+
+        let mut fns = vec![];
+        for defined_fn in &program.fns {
+            let has_return = defined_fn.code.iter().any(|x| matches!(x, Opcode::Return));
+            let has_return_top = defined_fn
+                .code
+                .iter()
+                .any(|x| matches!(x, Opcode::ReturnTop));
+            if has_return && has_return_top {
+                return Err(VerifyError::make(
+                    Some(&defined_fn.name),
+                    None,
+                    "Function both returns with and without a value".to_string(),
+                ));
+            }
+            fns.push(Signature {
+                arity: defined_fn.arity,
+                returns: has_return_top,
+            });
+        }
+
+        Ok(Self {
+            program,
+            fns,
+            natives,
+        })
+    }
+
+    fn verify_fn(&self, index: usize, defined_fn: &DefinedFn) -> Result<(), VerifyError> {
+        let error =
+            |pc: Option<usize>, msg: String| VerifyError::make(Some(&defined_fn.name), pc, msg);
+        let code = defined_fn.code.iter().collect::<Vec<&Opcode>>();
+
+        if defined_fn.arity > defined_fn.max_locals {
+            return Err(error(
+                None,
+                format!(
+                    "Arity {} exceeds max locals {}",
+                    defined_fn.arity, defined_fn.max_locals
+                ),
+            ));
+        }
+
+        for (pc, instr) in code.iter().enumerate() {
+            match instr {
+                Opcode::Jump(target) | Opcode::JumpIfFalse(target) if *target >= code.len() => {
+                    return Err(error(
+                        Some(pc),
+                        format!("Jump target {} out of range", target),
+                    ))
+                }
+                Opcode::Ldc(pool_index) if *pool_index >= self.program.pool.entries.len() => {
+                    return Err(error(
+                        Some(pc),
+                        format!("Pool index {} out of range", pool_index),
+                    ))
+                }
+                Opcode::LLoad(local) | Opcode::LStore(local) if *local >= defined_fn.max_locals => {
+                    return Err(error(
+                        Some(pc),
+                        format!("Local index {} out of range", local),
+                    ))
+                }
+                Opcode::Invoke(callee) | Opcode::TailInvoke(callee)
+                    if *callee >= self.fns.len() =>
+                {
+                    return Err(error(
+                        Some(pc),
+                        format!("Call to unknown function #{}", callee),
+                    ))
+                }
+                Opcode::InvokeNative(callee) if *callee >= self.natives.len() => {
+                    return Err(error(
+                        Some(pc),
+                        format!("Call to unknown native function #{}", callee),
+                    ))
+                }
+                Opcode::TailInvoke(callee)
+                    if self.fns[*callee].returns != self.fns[index].returns =>
+                {
+                    return Err(error(
+                        Some(pc),
+                        format!(
+                            "Tail call to '{}' doesn't match the return of the caller",
+                            self.program.fns[*callee].name
+                        ),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        // Walk every reachable path recording the stack depth before each instruction, paths
+        // joining at the same instruction must agree on it
+        let mut depths: Vec<Option<usize>> = vec![None; code.len()];
+        let mut pending: Vec<(usize, usize)> = vec![(0, 0)];
+
+        while let Some((pc, depth)) = pending.pop() {
+            let instr = match code.get(pc) {
+                Some(instr) => instr,
+                None => {
+                    return Err(error(
+                        Some(pc),
+                        "Execution falls off the end of code".to_string(),
+                    ))
+                }
+            };
+            match depths[pc] {
+                Some(known) if known == depth => continue,
+                Some(known) => {
+                    return Err(error(
+                        Some(pc),
+                        format!(
+                            "Inconsistent stack depth, {} on one path and {} on another",
+                            known, depth
+                        ),
+                    ))
+                }
+                None => depths[pc] = Some(depth),
+            }
+
+            let (pops, pushes) = self.stack_effect(instr);
+            if depth < pops {
+                return Err(error(
+                    Some(pc),
+                    format!(
+                        "Stack underflow, {} needs {} values but only {} are available",
+                        instr, pops, depth
+                    ),
+                ));
+            }
+            let depth = depth - pops + pushes;
+
+            match instr {
+                Opcode::Return | Opcode::ReturnTop | Opcode::TailInvoke(_) => {}
+                Opcode::Jump(target) => pending.push((*target, depth)),
+                Opcode::JumpIfFalse(target) => {
+                    pending.push((*target, depth));
+                    pending.push((pc + 1, depth));
+                }
+                _ => pending.push((pc + 1, depth)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// How many values an instruction pops and then pushes
+    fn stack_effect(&self, instr: &Opcode) -> (usize, usize) {
+        match instr {
+            Opcode::Nop | Opcode::Jump(_) | Opcode::Return => (0, 0),
+            Opcode::IAdd
+            | Opcode::ISub
+            | Opcode::IMul
+            | Opcode::IDiv
+            | Opcode::IRem
+            | Opcode::ICmpGT
+            | Opcode::ICmpGE
+            | Opcode::ICmpLT
+            | Opcode::ICmpLE
+            | Opcode::ICmpEQ
+            | Opcode::ICmpNE
+            | Opcode::FAdd
+            | Opcode::FSub
+            | Opcode::FMul
+            | Opcode::FDiv
+            | Opcode::FCmpGT
+            | Opcode::FCmpGE
+            | Opcode::FCmpLT
+            | Opcode::FCmpLE
+            | Opcode::FCmpEQ
+            | Opcode::FCmpNE
+            | Opcode::CmpEQ
+            | Opcode::CmpNE
+            | Opcode::And
+            | Opcode::Or => (2, 1),
+            Opcode::Not | Opcode::I2F | Opcode::F2I => (1, 1),
+            Opcode::Dup => (1, 2),
+            Opcode::Drop => (1, 0),
+            Opcode::Swap => (2, 2),
+            Opcode::Over => (2, 3),
+            Opcode::Rot => (3, 3),
+            Opcode::ReturnTop | Opcode::JumpIfFalse(_) | Opcode::LStore(_) => (1, 0),
+            Opcode::Ldc(_) | Opcode::LLoad(_) | Opcode::Push(_) => (0, 1),
+            Opcode::Invoke(index) => (self.fns[*index].arity, self.fns[*index].returns as usize),
+            Opcode::TailInvoke(index) => (self.fns[*index].arity, 0),
+            Opcode::InvokeNative(index) => (
+                self.natives[*index].arity,
+                self.natives[*index].returns as usize,
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytecode::PushOperand;
+    use crate::stdlib::list_native_fns;
+    use crate::vm::testing::program;
+
+    fn verify_err(program: Program) -> String {
+        verify(&program, &list_native_fns()).unwrap_err().msg
+    }
+
+    #[test]
+    fn ensure_well_formed_program_is_accepted() {
+        let program = program(vec![
+            (
+                "main",
+                0,
+                vec![
+                    Opcode::Push(PushOperand::Integer(1)),
+                    Opcode::Push(PushOperand::Boolean(true)),
+                    Opcode::JumpIfFalse(4),
+                    Opcode::Invoke(1),
+                    Opcode::InvokeNative(0),
+                    Opcode::Return,
+                ],
+            ),
+            (
+                "inc",
+                1,
+                vec![
+                    Opcode::LLoad(0),
+                    Opcode::Push(PushOperand::Integer(1)),
+                    Opcode::IAdd,
+                    Opcode::ReturnTop,
+                ],
+            ),
+        ]);
+        assert_eq!(Ok(()), verify(&program, &list_native_fns()));
+    }
+
+    #[test]
+    fn ensure_out_of_range_operands_are_rejected() {
+        let msg = verify_err(program(vec![("main", 0, vec![Opcode::Jump(7)])]));
+        assert_eq!("Jump target 7 out of range", msg);
+
+        let msg = verify_err(program(vec![(
+            "main",
+            0,
+            vec![Opcode::Ldc(0), Opcode::Return],
+        )]));
+        assert_eq!("Pool index 0 out of range", msg);
+
+        let msg = verify_err(program(vec![(
+            "main",
+            0,
+            vec![Opcode::LLoad(0), Opcode::Return],
+        )]));
+        assert_eq!("Local index 0 out of range", msg);
+
+        let msg = verify_err(program(vec![(
+            "main",
+            0,
+            vec![Opcode::Invoke(7), Opcode::Return],
+        )]));
+        assert_eq!("Call to unknown function #7", msg);
+    }
+
+    #[test]
+    fn ensure_stack_depth_is_checked_on_every_path() {
+        let msg = verify_err(program(vec![(
+            "main",
+            0,
+            vec![Opcode::IAdd, Opcode::Return],
+        )]));
+        assert_eq!(
+            "Stack underflow, [iadd] needs 2 values but only 0 are available",
+            msg
+        );
+
+        let msg = verify_err(program(vec![(
+            "main",
+            0,
+            vec![
+                Opcode::Push(PushOperand::Boolean(true)),
+                Opcode::JumpIfFalse(3),
+                Opcode::Push(PushOperand::Integer(1)),
+                Opcode::Return,
+            ],
+        )]));
+        assert_eq!(
+            "Inconsistent stack depth, 1 on one path and 0 on another",
+            msg
+        );
+
+        let msg = verify_err(program(vec![("main", 0, vec![Opcode::Nop])]));
+        assert_eq!("Execution falls off the end of code", msg);
+    }
+
+    #[test]
+    fn ensure_compiled_examples_are_accepted() {
+        let examples = concat!(env!("CARGO_MANIFEST_DIR"), "/examples");
+        for entry in std::fs::read_dir(examples).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|ext| ext != "bug") {
+                continue;
+            }
+            let source = std::fs::read_to_string(&path).unwrap();
+            let program = crate::compiler::compile(&source).unwrap();
+            assert_eq!(
+                Ok(()),
+                verify(&program, &list_native_fns()),
+                "{}",
+                path.display()
+            );
+        }
+    }
+}